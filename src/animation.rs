@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use crate::AppState;
+
+// Animation image par image dans l'atlas : une plage d'index [first, last] jouée à `fps` images par seconde
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationClip {
+    pub first: usize,
+    pub last: usize,
+    pub fps: f32,
+    pub looping: bool,
+}
+
+impl AnimationClip {
+    pub fn new(first: usize, last: usize, fps: f32, looping: bool) -> Self {
+        AnimationClip { first, last, fps, looping }
+    }
+
+    pub fn get_frame_count(&self) -> usize {
+        self.last.saturating_sub(self.first) + 1
+    }
+
+    pub fn get_duration(&self) -> f32 {
+        if self.fps <= 0.0 { return 0.0; }
+        self.get_frame_count() as f32 / self.fps
+    }
+
+    // Index dans l'atlas `elapsed` secondes après le début du clip,
+    // None quand un clip qui ne boucle pas est terminé
+    pub fn get_frame(&self, elapsed: f32) -> Option<usize> {
+        let frame = if self.fps <= 0.0 { 0 } else { (elapsed * self.fps) as usize };
+        let count = self.get_frame_count();
+        if self.looping {
+            Some(self.first + frame % count)
+        } else {
+            (frame < count).then_some(self.first + frame)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationState {
+    Walk,
+    Hit,
+    Death,
+}
+
+// Les clips d'une entité animée, un par état
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationSet {
+    pub walk: AnimationClip,
+    pub hit: AnimationClip,
+    pub death: AnimationClip,
+}
+
+impl AnimationSet {
+    pub fn get(&self, state: AnimationState) -> &AnimationClip {
+        match state {
+            AnimationState::Walk => &self.walk,
+            AnimationState::Hit => &self.hit,
+            AnimationState::Death => &self.death,
+        }
+    }
+}
+
+// Fait avancer TextureAtlas::index selon le clip de l'état en cours.
+// Hit revient à Walk une fois joué ; l'entité est supprimée à la fin de Death.
+#[derive(Component, Debug, Clone)]
+pub struct AnimationPlayer {
+    pub clips: AnimationSet,
+    pub state: AnimationState,
+    pub elapsed: f32,
+}
+
+impl AnimationPlayer {
+    pub fn new(clips: AnimationSet) -> Self {
+        AnimationPlayer { clips, state: AnimationState::Walk, elapsed: 0.0 }
+    }
+
+    // Repart du début du clip ; rien n'interrompt la mort
+    pub fn play(&mut self, state: AnimationState) {
+        if self.state == AnimationState::Death { return; }
+        self.state = state;
+        self.elapsed = 0.0;
+    }
+
+    pub fn get_clip(&self) -> &AnimationClip {
+        self.clips.get(self.state)
+    }
+}
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, play_animations
+            .run_if(in_state(AppState::Playing).or_else(in_state(AppState::Simulation))));
+    }
+}
+
+fn play_animations(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut AnimationPlayer, &mut TextureAtlas)>,
+) {
+    for (entity, mut player, mut atlas) in query.iter_mut() {
+        player.elapsed += time.delta_seconds();
+
+        let frame = match player.get_clip().get_frame(player.elapsed) {
+            Some(frame) => frame,
+            None => match player.state {
+                AnimationState::Death => {
+                    commands.entity(entity).despawn_recursive();
+                    continue;
+                }
+                _ => {
+                    player.play(AnimationState::Walk);
+                    player.clips.walk.first
+                }
+            },
+        };
+        // On évite de marquer l'atlas comme modifié à chaque frame
+        if atlas.index != frame {
+            atlas.index = frame;
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{
+    constants::tiles as Terrain,
+    game::{get_tile_type, TileType},
+};
+
+// Autotiling : à partir d'une carte "sémantique" (eau / herbe / route / départ / arrivée,
+// voir constants::tiles) on choisit l'id de tuile à afficher en regardant les voisins :
+// - les routes prennent le bon morceau droit ou le bon virage (ids 2 à 7) ; l'atlas n'a pas
+//   de sprite de carrefour, un embranchement prend donc l'un de ses virages,
+// - l'eau qui touche la terre prend la bonne transition et sa rotation (ids 8 à 19).
+
+// Voisins orthogonaux, dans l'ordre Haut, Bas, Droite, Gauche
+const ORTHOGONAL: [(i32, i32); 4] = [(0, -1), (0, 1), (1, 0), (-1, 0)];
+// Voisins en diagonale, dans l'ordre Bas-Droite, Bas-Gauche, Haut-Droite, Haut-Gauche
+const DIAGONAL: [(i32, i32); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
+
+pub fn autotile(terrain: &[Vec<u32>]) -> Vec<Vec<u32>> {
+    terrain.iter().enumerate()
+        .map(|(y, row)| {
+            (0..row.len()).map(|x| pick_tile(terrain, x, y)).collect()
+        })
+        .collect()
+}
+
+// Opération inverse (perte des détails) : retrouve le terrain d'une grille de tuiles
+pub fn terrain_from_tiles(tiles: &[Vec<u32>]) -> Vec<Vec<u32>> {
+    tiles.iter()
+        .map(|row| {
+            row.iter().map(|&tile_id| match get_tile_type(tile_id) {
+                TileType::Water => Terrain::WATER_TILE,
+                TileType::Grass => Terrain::GRASS_TILE,
+                TileType::Road => Terrain::ROAD_TILE,
+                TileType::Start => Terrain::START_TILE,
+                TileType::End => Terrain::END_TILE,
+            }).collect()
+        })
+        .collect()
+}
+
+fn terrain_at(terrain: &[Vec<u32>], x: i32, y: i32) -> Option<u32> {
+    if x < 0 || y < 0 { return None; }
+    terrain.get(y as usize)?.get(x as usize).copied()
+}
+
+fn is_road(terrain: Option<u32>) -> bool {
+    matches!(terrain, Some(Terrain::ROAD_TILE | Terrain::START_TILE | Terrain::END_TILE))
+}
+
+// Tout ce qui n'est pas de l'eau (le bord de la carte compte comme de l'eau)
+fn is_land(terrain: Option<u32>) -> bool {
+    terrain.is_some_and(|t| t != Terrain::WATER_TILE)
+}
+
+fn neighbors(terrain: &[Vec<u32>], x: usize, y: usize, offsets: [(i32, i32); 4], test: fn(Option<u32>) -> bool) -> [bool; 4] {
+    offsets.map(|(dx, dy)| test(terrain_at(terrain, x as i32 + dx, y as i32 + dy)))
+}
+
+fn pick_tile(terrain: &[Vec<u32>], x: usize, y: usize) -> u32 {
+    match terrain[y][x] {
+        Terrain::GRASS_TILE => 0,
+        Terrain::START_TILE => 20,
+        Terrain::END_TILE => 21,
+        Terrain::ROAD_TILE => {
+            let [up, down, right, left] = neighbors(terrain, x, y, ORTHOGONAL, is_road);
+            match (up, down, right, left) {
+                (_, true, true, _) => 4,  // Virage Droite-Bas
+                (_, true, _, true) => 5,  // Virage Gauche-Bas
+                (true, _, _, true) => 6,  // Virage Gauche-Haut
+                (true, _, true, _) => 7,  // Virage Haut-Droite
+                (true, _, _, _) | (_, true, _, _) => 3, // Vertical
+                _ => 2,                   // Horizontal (ou route isolée)
+            }
+        }
+        _ => pick_water_tile(terrain, x, y),
+    }
+}
+
+fn pick_water_tile(terrain: &[Vec<u32>], x: usize, y: usize) -> u32 {
+    let [up, down, right, left] = neighbors(terrain, x, y, ORTHOGONAL, is_land);
+    match (up, down, right, left) {
+        // Terre d'un seul côté : bord droit
+        (true, false, false, false) => 12,
+        (false, true, false, false) => 14,
+        (false, false, true, false) => 13,
+        (false, false, false, true) => 15,
+        // Terre sur deux côtés qui se touchent : coin intérieur
+        (false, true, false, true) => 8,
+        (true, false, false, true) => 9,
+        (true, false, true, false) => 10,
+        (false, true, true, false) => 11,
+        // Pas de terre autour : on regarde les diagonales (coin extérieur)
+        (false, false, false, false) => {
+            match neighbors(terrain, x, y, DIAGONAL, is_land) {
+                [true, false, false, false] => 18,
+                [false, true, false, false] => 19,
+                [false, false, true, false] => 17,
+                [false, false, false, true] => 16,
+                _ => 1, // Pleine eau (ou cas sans sprite adapté)
+            }
+        }
+        // Eau prise entre deux rives : pas de sprite, on garde l'eau pleine
+        _ => 1,
+    }
+}
@@ -0,0 +1,298 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{
+    AppState, GameAssets,
+    level::TILE_SIZE,
+    map::Path,
+    enemy::{Enemy, EnemyType, Health, Bounty, Dying, LeakDamage, PathFollower, FlightFollower, spawn_enemy_on_path, spawn_enemy_following},
+};
+
+// Boss : un ennemi d'un type donné, plus gros et plus résistant, avec une capacité spéciale.
+// Chaque capacité est un composant avec son propre système : en ajouter une ne touche
+// pas au déplacement des ennemis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BossType {
+    // Invoque régulièrement des sbires autour de lui
+    Necromancer,
+    // Soigne les ennemis proches
+    Healer,
+    // Ignore les premiers coups reçus
+    Shielded,
+}
+
+impl BossType {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            BossType::Necromancer => "Nécromancien",
+            BossType::Healer => "Guérisseur",
+            BossType::Shielded => "Chevalier au bouclier",
+        }
+    }
+
+    // Multiplie les points de vie du type d'ennemi de base
+    pub fn get_health_multiplier(&self) -> f32 {
+        match self {
+            BossType::Necromancer => 4.0,
+            BossType::Healer => 3.0,
+            BossType::Shielded => 2.5,
+        }
+    }
+
+    // Vies perdues si le boss atteint l'arrivée
+    pub fn get_leak_damage(&self) -> i32 {
+        match self {
+            BossType::Necromancer => 5,
+            BossType::Healer => 4,
+            BossType::Shielded => 5,
+        }
+    }
+
+    // Teinte du sprite, pour reconnaître le boss sur la carte
+    pub fn get_color(&self) -> Color {
+        match self {
+            BossType::Necromancer => Color::rgb(0.7, 0.4, 1.0),
+            BossType::Healer => Color::rgb(0.5, 1.0, 0.5),
+            BossType::Shielded => Color::rgb(0.6, 0.8, 1.0),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Boss {
+    pub boss_type: BossType,
+}
+
+// Nécromancien : `count` ennemis de type `minion` toutes les `timer` secondes
+#[derive(Component)]
+pub struct Summoner {
+    pub timer: Timer,
+    pub minion: EnemyType,
+    pub count: u32,
+}
+
+// Guérisseur : rend `amount` PV toutes les `timer` secondes aux ennemis à moins de `radius` pixels
+#[derive(Component)]
+pub struct Healer {
+    pub timer: Timer,
+    pub radius: f32,
+    pub amount: i32,
+}
+
+// Bouclier : les `hits` prochains projectiles ne font aucun dégât (voir projectile::move_projectiles)
+#[derive(Component)]
+pub struct Shield {
+    pub hits: u32,
+}
+
+// Barre de vie d'un boss dans le HUD
+#[derive(Component)]
+struct BossHud;
+
+#[derive(Component)]
+struct BossBar {
+    boss: Entity,
+}
+
+#[derive(Component)]
+struct BossBarFill {
+    boss: Entity,
+}
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(AppState::Playing), setup_boss_hud)
+            .add_systems(Update, (
+                summon_minions,
+                heal_nearby_enemies,
+                update_boss_hud,
+            ).run_if(in_state(AppState::Playing)))
+            .add_systems(OnExit(AppState::Playing), cleanup_boss_hud);
+    }
+}
+
+// Fait apparaître un boss au début du tronçon `segment`, comme spawn_enemy_on_path
+pub fn spawn_boss(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    path: &Path,
+    enemy_type: EnemyType,
+    boss_type: BossType,
+    segment: usize,
+    branch: Option<usize>,
+) -> Option<Entity> {
+    let entity = spawn_enemy_on_path(commands, assets, path, enemy_type, segment, branch)?;
+    let hp = (enemy_type.get_start_health() as f32 * boss_type.get_health_multiplier()) as i32;
+
+    let mut boss = commands.entity(entity);
+    boss.insert((
+        Boss { boss_type },
+        Health { current: hp, max: hp },
+        // Récompense à la mesure de ses points de vie
+        Bounty((enemy_type.get_bounty() as f32 * boss_type.get_health_multiplier()) as i32),
+        LeakDamage(boss_type.get_leak_damage()),
+        // Une fois et demie la taille d'une case (le sprite seul, pas sa barre de vie)
+        Sprite {
+            color: boss_type.get_color(),
+            custom_size: Some(Vec2::splat(TILE_SIZE * 1.5)),
+            ..default()
+        },
+        Name::new(boss_type.get_name()),
+    ));
+    match boss_type {
+        BossType::Necromancer => boss.insert(Summoner {
+            timer: Timer::from_seconds(4.0, TimerMode::Repeating),
+            minion: EnemyType::Orc,
+            count: 2,
+        }),
+        BossType::Healer => boss.insert(Healer {
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            radius: 3.0 * TILE_SIZE,
+            amount: 10,
+        }),
+        BossType::Shielded => boss.insert(Shield { hits: 8 }),
+    };
+
+    println!("Un boss arrive : {} ({} PV)", boss_type.get_name(), hp);
+    Some(entity)
+}
+
+// Les sbires apparaissent sur le boss et reprennent sa progression sur le chemin
+#[allow(clippy::type_complexity)]
+fn summon_minions(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    path: Res<Path>,
+    time: Res<Time>,
+    mut summoners: Query<(&Transform, &mut Summoner, Option<&PathFollower>, Option<&FlightFollower>), Without<Dying>>,
+) {
+    for (transform, mut summoner, path_follower, flight_follower) in summoners.iter_mut() {
+        if !summoner.timer.tick(time.delta()).just_finished() { continue; }
+
+        let position = transform.translation.truncate();
+        for _ in 0..summoner.count {
+            // Nécromancien volant avec des sbires au sol : ils partent du point de la route le plus proche
+            spawn_enemy_following(&mut commands, &assets, &path, summoner.minion, position, path_follower.copied(), flight_follower.copied());
+        }
+    }
+}
+
+fn heal_nearby_enemies(
+    time: Res<Time>,
+    mut healers: Query<(&Transform, &mut Healer), Without<Dying>>,
+    mut enemies: Query<(&Transform, &mut Health), With<Enemy>>,
+) {
+    for (healer_transform, mut healer) in healers.iter_mut() {
+        if !healer.timer.tick(time.delta()).just_finished() { continue; }
+
+        let center = healer_transform.translation.truncate();
+        let radius_sq = healer.radius * healer.radius;
+        for (transform, mut health) in enemies.iter_mut() {
+            // Un ennemi mort ce tour-ci ne revient pas à la vie
+            if health.current <= 0 { continue; }
+            if transform.translation.truncate().distance_squared(center) > radius_sq { continue; }
+            health.current = (health.current + healer.amount).min(health.max);
+        }
+    }
+}
+
+fn setup_boss_hud(mut commands: Commands) {
+    // Les barres s'empilent en haut au centre de l'écran
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        },
+        BossHud,
+    ));
+}
+
+// Ajoute une barre pour chaque nouveau boss, met les barres à jour et retire celles des boss morts ou disparus
+fn update_boss_hud(
+    mut commands: Commands,
+    hud_query: Query<Entity, With<BossHud>>,
+    new_bosses: Query<(Entity, &Boss), Added<Boss>>,
+    // Un boss qui joue son animation de mort n'a plus de barre
+    boss_query: Query<&Health, (With<Boss>, Without<Dying>)>,
+    bar_query: Query<(Entity, &BossBar)>,
+    mut fill_query: Query<(&mut Style, &BossBarFill)>,
+) {
+    let Ok(hud) = hud_query.get_single() else { return; };
+
+    for (entity, boss) in new_bosses.iter() {
+        let bar = commands.spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    margin: UiRect::bottom(Val::Px(6.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            BossBar { boss: entity },
+        )).with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                boss.boss_type.get_name(),
+                TextStyle {
+                    font_size: 22.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            // Fond noir, remplissage de la couleur du boss
+            parent.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(400.0),
+                    height: Val::Px(16.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                border_color: BorderColor(Color::BLACK),
+                ..default()
+            }).with_children(|parent| {
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        background_color: boss.boss_type.get_color().into(),
+                        ..default()
+                    },
+                    BossBarFill { boss: entity },
+                ));
+            });
+        }).id();
+        commands.entity(hud).add_child(bar);
+    }
+
+    for (mut style, fill) in fill_query.iter_mut() {
+        let Ok(health) = boss_query.get(fill.boss) else { continue; };
+        let percent = (health.current as f32 / health.max as f32).clamp(0.0, 1.0);
+        style.width = Val::Percent(percent * 100.0);
+    }
+
+    for (entity, bar) in bar_query.iter() {
+        if boss_query.get(bar.boss).is_err() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn cleanup_boss_hud(mut commands: Commands, hud_query: Query<Entity, With<BossHud>>) {
+    for entity in hud_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,431 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use crate::{
+    AppState,
+    GameAssets,
+    level::{self, Level, SelectedLevel, GridLayout, TerrainCell, TerrainModifier, HUD_HEIGHT, MAX_TILE_ID},
+    map::{spawn_tile, spawn_modifier, get_tile_sprites},
+    autotile::{autotile, terrain_from_tiles},
+    tiled::{self, TiledFormat},
+};
+
+// Marqueur pour tout ce qui appartient à l'éditeur
+#[derive(Component, Clone)]
+struct EditorComponent;
+
+// Marqueur des sprites de la carte (redessinés à chaque modification)
+#[derive(Component, Clone)]
+struct EditorMapTile;
+
+#[derive(Component)]
+struct PaletteButton {
+    tile_id: u32,
+}
+
+#[derive(Component)]
+struct EditorStatusText;
+
+// Tuile utilisée par la gomme (clic droit) et pour une carte vierge
+const ERASE_TILE: u32 = 0;
+// Fichier utilisé quand on édite une carte qui ne vient pas d'un fichier
+const DEFAULT_LEVEL_FILE: &str = "assets/levels/editor.level.ron";
+// Dossier des exports Tiled (hors de "assets/levels" pour ne pas dupliquer les niveaux)
+const TILED_EXPORT_FOLDER: &str = "assets/tiled";
+
+// Le niveau en cours d'édition
+#[derive(Resource)]
+struct EditorState {
+    level: Level,
+    // Handle de l'asset d'origine, mis à jour à la sauvegarde
+    handle: Option<Handle<Level>>,
+    file_path: String,
+    selected_tile: u32,
+    // Pinceau de terrain (touche M) : remplace la tuile choisie tant qu'il est actif
+    selected_modifier: Option<TerrainModifier>,
+    message: String,
+}
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(AppState::Editor), setup_editor)
+            .add_systems(Update, (
+                palette_interaction,
+                paint_tiles,
+                editor_shortcuts,
+                redraw_editor_map.run_if(resource_changed::<EditorState>),
+            ).chain().run_if(in_state(AppState::Editor)))
+            .add_systems(OnExit(AppState::Editor), cleanup_editor);
+    }
+}
+
+fn setup_editor(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    levels: Res<Assets<Level>>,
+    selected_level: Res<SelectedLevel>,
+) {
+    println!("Ouverture de l'éditeur !");
+
+    // On part du niveau sélectionné, ou d'une carte vierge de 20x20
+    let (level, handle, file_path) = match levels.get(&selected_level.handle) {
+        Some(level) => {
+            let file_path = selected_level.handle.path()
+                .map(|path| {
+                    let file_path = format!("assets/{}", path.path().display());
+                    // Pas d'export TMX : une carte .tmx est sauvegardée en .level.ron à côté
+                    if file_path.ends_with(".tmx") {
+                        format!("{}.level.ron", file_path.trim_end_matches(".tmx"))
+                    } else {
+                        file_path
+                    }
+                })
+                .unwrap_or_else(|| DEFAULT_LEVEL_FILE.to_string());
+            (level.clone(), Some(selected_level.handle.clone()), file_path)
+        }
+        None => (
+            Level::new("Nouveau niveau", vec![vec![ERASE_TILE; 20]; 20]),
+            None,
+            DEFAULT_LEVEL_FILE.to_string(),
+        ),
+    };
+
+    commands.insert_resource(GridLayout::from_level(&level));
+    commands.insert_resource(EditorState {
+        level,
+        handle,
+        file_path,
+        selected_tile: 2,
+        selected_modifier: None,
+        message: String::new(),
+    });
+
+    // --- Palette (barre du bas) : une tuile par id connu ---
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Px(HUD_HEIGHT),
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(0.0),
+                flex_wrap: FlexWrap::Wrap,
+                justify_content: JustifyContent::Center,
+                align_content: AlignContent::Center,
+                ..default()
+            },
+            background_color: Color::rgb_u8(220, 123, 15).into(),
+            ..default()
+        },
+        EditorComponent,
+    ))
+    .with_children(|parent| {
+        for tile_id in 0..=MAX_TILE_ID {
+            spawn_palette_button(parent, &assets, tile_id);
+        }
+    });
+
+    // --- Texte d'état (validation du chemin, sauvegarde...) ---
+    commands.spawn((
+        TextBundle::from_section("", TextStyle {
+            font_size: 18.0,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.7)),
+        EditorStatusText,
+        EditorComponent,
+    ));
+}
+
+fn spawn_palette_button(parent: &mut ChildBuilder, assets: &GameAssets, tile_id: u32) {
+    let ((base_index, base_rotation), overlay) = get_tile_sprites(tile_id);
+
+    parent.spawn((
+        ButtonBundle {
+            style: Style {
+                width: Val::Px(26.0),
+                height: Val::Px(26.0),
+                margin: UiRect::all(Val::Px(2.0)),
+                border: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            background_color: Color::GRAY.into(),
+            border_color: BorderColor(Color::GRAY),
+            ..default()
+        },
+        PaletteButton { tile_id },
+    ))
+    .with_children(|parent| {
+        // Même découpage que sur la carte : tuile de base + surcouche éventuelle
+        let mut layers = vec![(base_index, base_rotation)];
+        layers.extend(overlay);
+        for (index, rotation) in layers {
+            parent.spawn(AtlasImageBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                texture_atlas: TextureAtlas { layout: assets.sprite_atlas_layout.clone(), index },
+                image: UiImage::new(assets.sprite_atlas.clone()),
+                transform: Transform::from_rotation(rotation),
+                ..default()
+            });
+        }
+    });
+}
+
+fn palette_interaction(
+    mut interaction_query: Query<(&Interaction, &PaletteButton, &mut BorderColor)>,
+    mut state: ResMut<EditorState>,
+) {
+    for (interaction, button, mut border_color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                if state.selected_tile != button.tile_id || state.selected_modifier.is_some() {
+                    state.selected_tile = button.tile_id;
+                    state.selected_modifier = None;
+                }
+                *border_color = BorderColor(Color::BLACK);
+            }
+            Interaction::Hovered => {
+                *border_color = BorderColor(Color::WHITE);
+            }
+            Interaction::None => {
+                let is_selected = state.selected_tile == button.tile_id;
+                *border_color = BorderColor(if is_selected { Color::BLACK } else { Color::GRAY });
+            }
+        }
+    }
+}
+
+// Clic gauche : peindre la tuile choisie (ou le terrain), clic droit : effacer (on peut glisser)
+fn paint_tiles(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+    layout: Res<GridLayout>,
+    mut state: ResMut<EditorState>,
+) {
+    let left = mouse_input.pressed(MouseButton::Left);
+    if !left && !mouse_input.pressed(MouseButton::Right) { return; }
+
+    let (camera, camera_transform) = camera_q.single();
+    let Ok(window) = windows.get_single() else { return };
+    // La palette n'est pas sur la carte
+    let Some((x, y)) = layout.cursor_to_grid(window, camera, camera_transform) else { return };
+
+    if let Some(modifier) = state.selected_modifier {
+        // Comme pour les tuiles : on ne touche à l'état que si la case change vraiment
+        let wanted = left.then_some(modifier);
+        if state.level.get_modifier(x, y) != wanted {
+            paint_modifier(&mut state, x, y, wanted);
+        }
+        return;
+    }
+
+    let tile_id = if left { state.selected_tile } else { ERASE_TILE };
+
+    // On ne touche à l'état (et donc au redessin) que si la tuile change vraiment
+    if state.level.tiles[y][x] == tile_id { return; }
+
+    // Plusieurs départs possibles, mais une seule arrivée : l'ancienne est effacée
+    if tile_id == 21 {
+        for row in state.level.tiles.iter_mut() {
+            for tile in row.iter_mut() {
+                if *tile == tile_id { *tile = ERASE_TILE; }
+            }
+        }
+    }
+    state.level.tiles[y][x] = tile_id;
+    state.message.clear();
+}
+
+// Pinceau de terrain : pose (Some) ou enlève (None) le modificateur de la case
+fn paint_modifier(state: &mut EditorState, x: usize, y: usize, wanted: Option<TerrainModifier>) {
+    state.level.modifiers.retain(|cell| cell.x != x || cell.y != y);
+    if let Some(modifier) = wanted {
+        state.level.modifiers.push(TerrainCell { x, y, modifier });
+    }
+    state.message.clear();
+}
+
+// S : sauvegarder, L : recharger depuis le fichier, T : autotiling,
+// E : exporter pour Tiled, M : pinceau de terrain suivant, Échap : retour au menu
+fn editor_shortcuts(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<EditorState>,
+    mut levels: ResMut<Assets<Level>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(AppState::Menu);
+    }
+
+    if keys.just_pressed(KeyCode::KeyS) {
+        let result = write_level_file(&state.file_path, &state.level);
+
+        state.message = match result {
+            Ok(()) => {
+                // Le jeu utilise directement la nouvelle version, sans recharger les assets
+                if let Some(handle) = state.handle.clone() {
+                    levels.insert(handle, state.level.clone());
+                }
+                format!("Sauvegardé dans {}", state.file_path)
+            }
+            Err(error) => format!("Échec de la sauvegarde : {error}"),
+        };
+        println!("{}", state.message);
+    }
+
+    // On ne garde que le type de chaque tuile et on laisse l'autotiling choisir
+    // les transitions eau/terre et les virages de la route
+    if keys.just_pressed(KeyCode::KeyT) {
+        let terrain = terrain_from_tiles(&state.level.tiles);
+        state.level.tiles = autotile(&terrain);
+        state.message = "Autotiling appliqué".to_string();
+    }
+
+    if keys.just_pressed(KeyCode::KeyE) {
+        let file_name = state.file_path.rsplit('/').next().unwrap_or_default();
+        let stem = file_name.trim_end_matches(".level.ron").trim_end_matches(".tmj");
+        let export_path = format!("{TILED_EXPORT_FOLDER}/{stem}.tmj");
+        let result = std::fs::create_dir_all(TILED_EXPORT_FOLDER)
+            .map_err(|error| error.to_string())
+            .and_then(|()| write_level_file(&export_path, &state.level));
+
+        state.message = match result {
+            Ok(()) => format!("Exporté pour Tiled dans {export_path}"),
+            Err(error) => format!("Échec de l'export : {error}"),
+        };
+        println!("{}", state.message);
+    }
+
+    // Tuiles -> boue -> pont -> accélérateur -> tuiles
+    if keys.just_pressed(KeyCode::KeyM) {
+        state.selected_modifier = match state.selected_modifier {
+            None => Some(TerrainModifier::ALL[0]),
+            Some(modifier) => TerrainModifier::ALL.into_iter()
+                .skip_while(|&other| other != modifier)
+                .nth(1),
+        };
+    }
+
+    if keys.just_pressed(KeyCode::KeyL) {
+        let result = read_level_file(&state.file_path);
+
+        match result {
+            Ok(level) => {
+                state.level = level;
+                state.message = format!("Chargé depuis {}", state.file_path);
+            }
+            Err(error) => state.message = format!("Échec du chargement : {error}"),
+        }
+        println!("{}", state.message);
+    }
+}
+
+// Lecture / écriture d'un niveau selon l'extension du fichier : .tmj (Tiled) ou .level.ron
+fn read_level_file(path: &str) -> Result<Level, String> {
+    let bytes = std::fs::read(path).map_err(|error| error.to_string())?;
+    if path.ends_with(".tmj") {
+        tiled::parse_tiled(&bytes, TiledFormat::Tmj, "").map_err(|error| error.to_string())
+    } else {
+        level::parse_level(&bytes).map_err(|error| error.to_string())
+    }
+}
+
+fn write_level_file(path: &str, level: &Level) -> Result<(), String> {
+    let text = if path.ends_with(".tmj") {
+        tiled::export_tmj(level).map_err(|error| error.to_string())?
+    } else {
+        level::serialize_level(level).map_err(|error| error.to_string())?
+    };
+    std::fs::write(path, text).map_err(|error| error.to_string())
+}
+
+// Redessine la carte et revalide le chemin à chaque modification
+fn redraw_editor_map(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    state: Res<EditorState>,
+    mut layout: ResMut<GridLayout>,
+    tiles_query: Query<Entity, With<EditorMapTile>>,
+    mut status_query: Query<&mut Text, With<EditorStatusText>>,
+) {
+    for entity in tiles_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // Un fichier rechargé peut avoir d'autres dimensions
+    let new_layout = GridLayout::from_level(&state.level);
+    if new_layout.width != layout.width || new_layout.height != layout.height {
+        *layout = new_layout;
+    }
+
+    for (y, row) in state.level.tiles.iter().enumerate() {
+        for (x, &tile_id) in row.iter().enumerate() {
+            spawn_tile(&mut commands, &assets, layout.grid_to_world(x, y), tile_id, (EditorMapTile, EditorComponent));
+        }
+    }
+    for cell in state.level.modifiers.iter() {
+        spawn_modifier(&mut commands, layout.grid_to_world(cell.x, cell.y), cell.modifier, (EditorMapTile, EditorComponent));
+    }
+
+    // Validation en direct, avec le même parcours que setup_game
+    let validation = match level::validate_level(&state.level) {
+        Ok(path_graph) => {
+            // On marque les cases du chemin trouvé (toutes les branches)
+            let cells = path_graph.cells();
+            for cell in cells.iter() {
+                let pos = layout.grid_to_world(cell.x, cell.y);
+                commands.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::rgba(1.0, 1.0, 1.0, 0.5),
+                            custom_size: Some(Vec2::splat(6.0)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(pos.x, pos.y, 0.5),
+                        ..default()
+                    },
+                    EditorMapTile,
+                    EditorComponent,
+                ));
+            }
+            format!(
+                "Chemin valide : {} cases, {} départ(s), {} tronçon(s)",
+                cells.len(), path_graph.starts.len(), path_graph.segments.len()
+            )
+        }
+        Err(error) => format!("Niveau invalide : {error}"),
+    };
+
+    for mut text in status_query.iter_mut() {
+        let brush = match state.selected_modifier {
+            Some(modifier) => format!("terrain {} (x{})", modifier.get_name(), modifier.get_speed_multiplier()),
+            None => format!("tuile {}", state.selected_tile),
+        };
+        text.sections[0].value = format!(
+            "{} - {}\n{}\n{}\nClic gauche : peindre | Clic droit : effacer | M : terrain\nS : sauver | L : recharger | T : autotiling | E : export Tiled | Échap : menu",
+            state.level.name, brush, validation, state.message
+        );
+    }
+}
+
+fn cleanup_editor(mut commands: Commands, query: Query<Entity, With<EditorComponent>>) {
+    println!("Nettoyage de l'éditeur...");
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<EditorState>();
+    commands.remove_resource::<GridLayout>();
+}
@@ -1,5 +1,15 @@
-use bevy::prelude::*;
-use crate::{AppState, GameAssets, game::Path, constants::enemies as EnemyConstants, game::PlayerStats}; 
+use bevy::{prelude::*, ecs::system::EntityCommands};
+use serde::{Deserialize, Serialize};
+use crate::{
+    AppState, GameAssets,
+    level::{GridLayout, TILE_SIZE},
+    map::{CurrentMap, Path, PathRng},
+    constants::enemies as EnemyConstants,
+    game::{PlayerStats, Difficulty, MoneyChanged, LivesChanged},
+    tower::DamageType,
+    status::StatusEffects,
+    animation::{AnimationClip, AnimationPlayer, AnimationSet, AnimationState},
+};
 
 // Le component Ennemi (juste avec une vitesse)
 #[derive(Component)]
@@ -17,71 +27,306 @@ pub struct Health {
 #[derive(Component)]
 pub struct HealthBar;
 
-// Le path finding marche en suivant a chaque fois le point suivant
-// sur un chemin prédéfini (Path). On garde l'index du point actuel.
-#[derive(Component)]
+// Part des dégâts ignorée pour chaque type (0.0 = dégâts complets, 1.0 = immunité)
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Resistances {
+    pub physical: f32,
+    pub explosive: f32,
+    pub magic: f32,
+}
+
+impl Resistances {
+    pub fn get(&self, damage_type: DamageType) -> f32 {
+        match damage_type {
+            DamageType::Physical => self.physical,
+            DamageType::Explosive => self.explosive,
+            DamageType::Magic => self.magic,
+        }
+    }
+}
+
+// Le path finding marche en suivant la courbe lisse de chaque tronçon du chemin (Path).
+// On garde le tronçon actuel et la distance parcourue sur sa courbe.
+#[derive(Component, Debug, Clone, Copy)]
 pub struct PathFollower {
-    pub path_index: usize,
+    pub segment: usize,
+    pub distance: f32,
+    // Branche imposée à chaque embranchement (données de vague), sinon tirage pondéré
+    pub branch: Option<usize>,
+}
+
+// Les ennemis volants ignorent la route : ils vont tout droit vers l'arrivée,
+// ou suivent les points de passage du niveau (Path::flight_points)
+#[derive(Component)]
+pub struct Flying;
+
+// Index du point de passage visé dans Path::flight_points
+#[derive(Component, Debug, Clone, Copy)]
+pub struct FlightFollower {
+    pub waypoint: usize,
+}
+
+// Ennemi tué qui joue son animation de mort : il n'a plus le composant Enemy
+// (plus de déplacement, plus ciblé par les tours) et disparaît à la fin de l'animation.
+// Les déclencheurs de mort (SpawnOnDeath) réagissent à l'ajout de ce marqueur.
+#[derive(Component)]
+pub struct Dying;
+
+// Déclencheur de mort : l'ennemi se scinde en `count` ennemis de type `enemy`, plus petits
+// (`scale` de la taille, des PV et de la récompense), qui reprennent sa progression sur le chemin
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpawnOnDeath {
+    pub enemy: EnemyType,
+    pub count: u32,
+    pub scale: f32,
+}
+
+impl SpawnOnDeath {
+    pub fn new(enemy: EnemyType, count: u32, scale: f32) -> Self {
+        SpawnOnDeath { enemy, count, scale }
+    }
 }
 
-// gère le temps entre les apparitions (Spawning)
-#[derive(Resource)]
-struct EnemySpawnTimer {
-    timer: Timer,
+// Écart (en pixels) entre deux enfants d'un ennemi scindé, pour qu'ils ne se superposent pas
+const SPLIT_SPACING: f32 = 10.0;
+
+// Or gagné quand l'ennemi est tué (avant le multiplicateur de difficulté)
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Bounty(pub i32);
+
+// Vies perdues quand l'ennemi atteint l'arrivée (avant le multiplicateur de difficulté)
+#[derive(Component, Debug, Clone, Copy)]
+pub struct LeakDamage(pub i32);
+
+// Distance parcourue (en pixels) depuis l'apparition, au sol comme en vol :
+// permet par exemple de savoir quel ennemi est le plus proche de l'arrivée
+#[derive(Component, Default)]
+pub struct DistanceTravelled(pub f32);
+
+// Position d'un élément de la barre de vie par rapport à l'ennemi : la barre reste droite
+// au-dessus de sa tête quelle que soit l'orientation de l'ennemi
+#[derive(Component)]
+struct HealthBarAnchor {
+    offset: Vec3,
+}
+
+// Les 4 types d'ennemis (mêmes valeurs que constants::enemies)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component, Serialize, Deserialize)]
+pub enum EnemyType {
+    Orc = 0,
+    Bat = 1,
+    Knight = 2,
+    Wolf = 3,
 }
 
+impl EnemyType {
+    pub const ALL: [EnemyType; 4] = [EnemyType::Orc, EnemyType::Bat, EnemyType::Knight, EnemyType::Wolf];
+
+    // Vitesse en pixels par seconde (les constantes Java sont en pixels par tick)
+    pub fn get_speed(&self) -> f32 {
+        EnemyConstants::get_speed(*self as u32) * 100.0
+    }
+
+    pub fn get_start_health(&self) -> i32 {
+        EnemyConstants::get_starthealth(*self as u32) as i32
+    }
+
+    // Ligne 1 de l'atlas : orc, chauve-souris, chevalier, loup
+    pub fn get_sprite_index(&self) -> usize {
+        10 + *self as usize
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            EnemyType::Orc => "Orc",
+            EnemyType::Bat => "Bat",
+            EnemyType::Knight => "Knight",
+            EnemyType::Wolf => "Wolf",
+        }
+    }
+
+    pub fn is_flying(&self) -> bool {
+        *self == EnemyType::Bat
+    }
+
+    // Plages d'index dans l'atlas. L'atlas n'a qu'une image par ennemi pour la marche ;
+    // le coup et la mort piochent dans les images d'impact, d'explosion et d'étincelles de la ligne 2
+    // (21 : flamme, 22-23 : explosion, 24-25 : étincelles).
+    pub fn get_animations(&self) -> AnimationSet {
+        let sprite = self.get_sprite_index();
+        let walk = AnimationClip::new(sprite, sprite, 1.0, true);
+        match self {
+            EnemyType::Orc => AnimationSet {
+                walk,
+                hit: AnimationClip::new(21, 21, 10.0, false),
+                death: AnimationClip::new(21, 23, 12.0, false),
+            },
+            // La chauve-souris se disperse en étincelles
+            EnemyType::Bat => AnimationSet {
+                walk,
+                hit: AnimationClip::new(24, 24, 12.0, false),
+                death: AnimationClip::new(24, 25, 8.0, false),
+            },
+            // L'armure étincelle sous les coups, et sa chute dure plus longtemps
+            EnemyType::Knight => AnimationSet {
+                walk,
+                hit: AnimationClip::new(23, 23, 8.0, false),
+                death: AnimationClip::new(22, 25, 10.0, false),
+            },
+            // Le loup, rapide, meurt vite
+            EnemyType::Wolf => AnimationSet {
+                walk,
+                hit: AnimationClip::new(21, 21, 14.0, false),
+                death: AnimationClip::new(21, 22, 14.0, false),
+            },
+        }
+    }
+
+    pub fn get_bounty(&self) -> i32 {
+        match self {
+            EnemyType::Orc => 5,
+            EnemyType::Bat => 5,
+            EnemyType::Knight => 15,
+            EnemyType::Wolf => 8,
+        }
+    }
+
+    pub fn get_leak_damage(&self) -> i32 {
+        match self {
+            EnemyType::Knight => 2,
+            EnemyType::Orc | EnemyType::Bat | EnemyType::Wolf => 1,
+        }
+    }
+
+    // L'armure du chevalier arrête une partie des flèches et des boulets, la magie passe ;
+    // les bombes n'atteignent pas la chauve-souris ; le loup craint un peu moins les sorts
+    pub fn get_resistances(&self) -> Resistances {
+        match self {
+            EnemyType::Orc => Resistances::default(),
+            EnemyType::Bat => Resistances { physical: 0.0, explosive: 1.0, magic: 0.0 },
+            EnemyType::Knight => Resistances { physical: 0.6, explosive: 0.3, magic: 0.0 },
+            EnemyType::Wolf => Resistances { physical: 0.0, explosive: 0.0, magic: 0.25 },
+        }
+    }
+}
 
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
         app
-            .insert_resource(EnemySpawnTimer {
-                timer: Timer::from_seconds(1.5, TimerMode::Repeating), // Un peu plus rapide
-            })
+            .init_resource::<PathRng>()
+            // Les apparitions sont gérées par les vagues (wave::WaveManager)
             .add_systems(Update, 
-                (spawn_enemies, move_enemies, animate_enemy_rotation, enemy_death_system, update_health_bars)
+                (move_enemies, move_flying_enemies, enemy_death_system, split_on_death.after(enemy_death_system), update_health_bars, keep_health_bars_upright)
                 .run_if(in_state(AppState::Playing).or_else(in_state(AppState::Simulation)))
             );
     }
 }
 
-fn spawn_enemies(
-    mut commands: Commands,
-    assets: Res<GameAssets>,
-    path: Res<Path>,
-    time: Res<Time>,
-    mut spawn_timer: ResMut<EnemySpawnTimer>,
-) {
-    spawn_timer.timer.tick(time.delta());
-
-    if spawn_timer.timer.just_finished() {
-        if path.points.is_empty() { return; }
-        
-        let start_pos = path.points[0]; 
-        let hp = 85; 
-        let speed = 0.5 * 100.0; 
-
-        // On spawn l'ennemi
-        commands.spawn((
-            SpriteSheetBundle {
-                texture: assets.sprite_atlas.clone(),
-                atlas: TextureAtlas {
-                    layout: assets.sprite_atlas_layout.clone(),
-                    index: 10, // Orc
-                },
-                transform: Transform::from_xyz(start_pos.x, start_pos.y, 1.0), 
-                ..default()
+// Fait apparaître un ennemi au début du tronçon `segment` : les ennemis au sol suivent
+// le chemin (en prenant `branch` à chaque embranchement si elle est imposée),
+// les volants suivent Path::flight_points
+pub fn spawn_enemy_on_path(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    path: &Path,
+    enemy_type: EnemyType,
+    segment: usize,
+    branch: Option<usize>,
+) -> Option<Entity> {
+    let start_pos = path.get_point(segment, 0)?;
+    let mut enemy = spawn_enemy(commands, assets, enemy_type, start_pos);
+
+    if enemy_type.is_flying() {
+        // Pas de PathFollower : la chauve-souris ne suit pas la route
+        enemy.insert((Flying, FlightFollower { waypoint: 0 }));
+    } else {
+        enemy.insert(PathFollower { segment, distance: 0.0, branch });
+    }
+    Some(enemy.id())
+}
+
+// Fait apparaître un ennemi en cours de route, là où en est un autre (sbire, ennemi scindé) :
+// il reprend sa progression au lieu de repartir du début du chemin. Un ennemi au sol sans
+// progression à reprendre (né d'un volant) part du point de la route le plus proche.
+// None seulement si le niveau n'a pas de route.
+pub fn spawn_enemy_following<'a>(
+    commands: &'a mut Commands,
+    assets: &GameAssets,
+    path: &Path,
+    enemy_type: EnemyType,
+    position: Vec2,
+    path_follower: Option<PathFollower>,
+    flight_follower: Option<FlightFollower>,
+) -> Option<EntityCommands<'a>> {
+    if enemy_type.is_flying() {
+        let waypoint = flight_follower.map_or(0, |follower| follower.waypoint);
+        let mut enemy = spawn_enemy(commands, assets, enemy_type, position);
+        enemy.insert((Flying, FlightFollower { waypoint }));
+        Some(enemy)
+    } else {
+        let follower = path_follower.or_else(|| get_nearest_follower(path, position))?;
+        let mut enemy = spawn_enemy(commands, assets, enemy_type, position);
+        enemy.insert(follower);
+        Some(enemy)
+    }
+}
+
+// Progression sur la route correspondant à `position` : le point de la route le plus proche
+pub fn get_nearest_follower(path: &Path, position: Vec2) -> Option<PathFollower> {
+    let (segment, distance) = path.get_nearest(position)?;
+    Some(PathFollower { segment, distance, branch: None })
+}
+
+// Progression du `index`-ième enfant d'un ennemi scindé : chacun un peu en arrière du précédent,
+// sans reculer avant le début du tronçon
+fn get_split_follower(parent: &PathFollower, index: u32) -> PathFollower {
+    PathFollower {
+        distance: (parent.distance - index as f32 * SPLIT_SPACING).max(0.0),
+        ..*parent
+    }
+}
+
+// Construit un ennemi à partir de son type : stats des constantes, sprite, nom et barre de vie.
+// Le déplacement (PathFollower, FlightFollower...) est ajouté par l'appelant.
+pub fn spawn_enemy<'a>(
+    commands: &'a mut Commands,
+    assets: &GameAssets,
+    enemy_type: EnemyType,
+    position: Vec2,
+) -> EntityCommands<'a> {
+    let hp = enemy_type.get_start_health();
+    // Les volants passent au-dessus des ennemis au sol
+    let z = if enemy_type.is_flying() { 1.5 } else { 1.0 };
+
+    let mut enemy = commands.spawn((
+        SpriteSheetBundle {
+            texture: assets.sprite_atlas.clone(),
+            atlas: TextureAtlas {
+                layout: assets.sprite_atlas_layout.clone(),
+                index: enemy_type.get_sprite_index(),
             },
-            Enemy { speed },
-            Health { current: hp, max: hp },
-            PathFollower { path_index: 1 }, 
-            Name::new("Orc"),
-        ))
-        // ON AJOUTE DES ENFANTS (CHILDREN) À L'ENTITÉ
-        .with_children(|parent| {
-            // 1. Fond de la barre (Noir, un peu plus grand)
-            parent.spawn(SpriteBundle {
+            transform: Transform::from_xyz(position.x, position.y, z),
+            ..default()
+        },
+        Enemy { speed: enemy_type.get_speed() },
+        Health { current: hp, max: hp },
+        enemy_type.get_resistances(),
+        StatusEffects::default(),
+        Bounty(enemy_type.get_bounty()),
+        LeakDamage(enemy_type.get_leak_damage()),
+        AnimationPlayer::new(enemy_type.get_animations()),
+        enemy_type,
+        DistanceTravelled::default(),
+        Name::new(enemy_type.get_name()),
+    ));
+
+    // ON AJOUTE DES ENFANTS (CHILDREN) À L'ENTITÉ
+    enemy.with_children(|parent| {
+        // 1. Fond de la barre (Noir, un peu plus grand)
+        parent.spawn((
+            SpriteBundle {
                 sprite: Sprite {
                     color: Color::BLACK,
                     custom_size: Some(Vec2::new(22.0, 6.0)),
@@ -89,90 +334,158 @@ fn spawn_enemies(
                 },
                 transform: Transform::from_xyz(0.0, 20.0, 0.1), // Au dessus de la tête
                 ..default()
-            });
-
-            // 2. Barre de vie (Rouge)
-            parent.spawn((
-                SpriteBundle {
-                    sprite: Sprite {
-                        color: Color::RED,
-                        custom_size: Some(Vec2::new(20.0, 4.0)), // Taille max
-                        ..default()
-                    },
-                    // On décale légèrement en z (0.2) pour être devant le noir
-                    transform: Transform::from_xyz(0.0, 20.0, 0.2), 
+            },
+            HealthBarAnchor { offset: Vec3::new(0.0, 20.0, 0.1) },
+        ));
+
+        // 2. Barre de vie (Rouge)
+        parent.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::RED,
+                    custom_size: Some(Vec2::new(20.0, 4.0)), // Taille max
                     ..default()
                 },
-                HealthBar, // Marqueur pour la mise à jour
-            ));
-        });
-    }
+                // On décale légèrement en z (0.2) pour être devant le noir
+                transform: Transform::from_xyz(0.0, 20.0, 0.2), 
+                ..default()
+            },
+            HealthBar, // Marqueur pour la mise à jour
+            HealthBarAnchor { offset: Vec3::new(0.0, 20.0, 0.2) },
+        ));
+    });
+
+    enemy
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn move_enemies(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut Transform, &Enemy, &mut PathFollower)>,
+    mut query: Query<(Entity, &mut Transform, &Enemy, &mut PathFollower, &mut DistanceTravelled, Option<&StatusEffects>, &LeakDamage)>,
     path: Res<Path>,
     time: Res<Time>,
     mut stats: ResMut<PlayerStats>, // <--- Ajoutez ceci
+    difficulty: Res<Difficulty>,
+    mut lives_events: EventWriter<LivesChanged>,
+    mut rng: ResMut<PathRng>,
+    map: Option<Res<CurrentMap>>,
+    layout: Option<Res<GridLayout>>,
 ) {
-    if path.points.is_empty() { return; }
-    for (entity, mut transform, enemy, mut follower) in query.iter_mut() {
-        if follower.path_index >= path.points.len() {
-            stats.lives -= 1; // Perte de vie
+    if path.is_empty() { return; }
+    'enemies: for (entity, mut transform, enemy, mut follower, mut travelled, status, leak_damage) in query.iter_mut() {
+        // Boue, pont ou accélérateur sous l'ennemi
+        let multiplier = match (&map, &layout) {
+            (Some(map), Some(layout)) => layout.world_to_grid(transform.translation.truncate())
+                .map_or(1.0, |(x, y)| map.level.get_speed_multiplier(x, y)),
+            _ => 1.0,
+        };
+        let step = get_effective_speed(enemy, status) * multiplier * time.delta_seconds();
+        follower.distance += step;
+        travelled.0 += step;
+
+        // Fin du tronçon : on passe au suivant (embranchement) avec la distance restante,
+        // ou on a atteint l'arrivée
+        loop {
+            let Some(length) = path.segments.get(follower.segment).map(|segment| segment.curve.length()) else { continue 'enemies; };
+            if follower.distance <= length { break; }
+
+            let roll = rng.0.next_f32();
+            match path.choose_next(follower.segment, follower.branch, roll) {
+                Some(next) => {
+                    // Le tronçon suivant commence au carrefour où finit celui-ci
+                    follower.segment = next;
+                    follower.distance -= length;
+                }
+                None => {
+                    // Perte de vie
+                    let lives = difficulty.scale_leak_damage(leak_damage.0);
+                    stats.lives -= lives;
+                    lives_events.send(LivesChanged { amount: -lives, position: Some(transform.translation.truncate()) });
+                    commands.entity(entity).despawn_recursive();
+                    continue 'enemies;
+                }
+            }
+        }
+
+        let (position, direction) = path.segments[follower.segment].curve.sample(follower.distance);
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        if direction != Vec2::ZERO {
+            transform.rotation = get_facing_rotation(direction);
+        }
+    }
+}
+
+// Les volants vont de point de passage en point de passage, le dernier est l'arrivée
+#[allow(clippy::type_complexity)]
+fn move_flying_enemies(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &Enemy, &mut FlightFollower, &mut DistanceTravelled, Option<&StatusEffects>, &LeakDamage), With<Flying>>,
+    path: Res<Path>,
+    time: Res<Time>,
+    mut stats: ResMut<PlayerStats>,
+    difficulty: Res<Difficulty>,
+    mut lives_events: EventWriter<LivesChanged>,
+) {
+    for (entity, mut transform, enemy, mut follower, mut travelled, status, leak_damage) in query.iter_mut() {
+        let Some(&target) = path.flight_points.get(follower.waypoint) else {
+            // Perte de vie
+            let lives = difficulty.scale_leak_damage(leak_damage.0);
+            stats.lives -= lives;
+            lives_events.send(LivesChanged { amount: -lives, position: Some(transform.translation.truncate()) });
             commands.entity(entity).despawn_recursive();
             continue;
-        }
+        };
 
-        let target = path.points[follower.path_index];
         let direction = target - transform.translation.truncate();
-        let distance = direction.length();
-        let step = enemy.speed * time.delta_seconds();
+        let step = get_effective_speed(enemy, status) * time.delta_seconds();
 
-        if distance <= step {
+        if direction.length() <= step {
             transform.translation.x = target.x;
             transform.translation.y = target.y;
-            follower.path_index += 1;
+            follower.waypoint += 1;
+            travelled.0 += direction.length();
         } else {
             let movement = direction.normalize() * step;
             transform.translation.x += movement.x;
             transform.translation.y += movement.y;
+            travelled.0 += step;
+        }
+
+        // Même orientation que les ennemis au sol
+        if direction != Vec2::ZERO {
+            transform.rotation = get_facing_rotation(direction);
         }
     }
 }
 
-fn animate_enemy_rotation(
-    mut query: Query<(&mut Transform, &PathFollower)>,
-    path: Res<Path>,
-) {
-    if path.points.is_empty() { return; }
-
-    for (mut transform, follower) in query.iter_mut() {
-        if follower.path_index < path.points.len() {
-            let target = path.points[follower.path_index];
-            let current = transform.translation.truncate();
-            let diff = target - current;
-
-            if diff.x.abs() > diff.y.abs() {
-                // Note: La rotation affecte aussi la barre de vie !
-                // Pour éviter que la barre tourne, il faudrait contrer la rotation
-                // ou gérer le sprite séparément. Pour l'instant, simple rotation Y (miroir).
-                if diff.x > 0.0 { transform.rotation = Quat::IDENTITY; } 
-                else { transform.rotation = Quat::from_rotation_y(std::f32::consts::PI); }
-            } 
-        }
+// Vitesse de l'ennemi une fois ses effets appliqués (ralenti, étourdi)
+pub fn get_effective_speed(enemy: &Enemy, status: Option<&StatusEffects>) -> f32 {
+    enemy.speed * status.map_or(1.0, |status| status.get_speed_multiplier())
+}
+
+// Les sprites regardent vers la droite : on les retourne (miroir) quand l'ennemi va vers
+// la gauche, puis on les incline dans le sens du déplacement
+pub fn get_facing_rotation(direction: Vec2) -> Quat {
+    if direction.x < 0.0 {
+        Quat::from_rotation_y(std::f32::consts::PI) * Quat::from_rotation_z(direction.y.atan2(-direction.x))
+    } else {
+        Quat::from_rotation_z(direction.y.atan2(direction.x))
     }
 }
 
 fn update_health_bars(
     // On cherche les entités qui sont des barres de vie et qui ont un Parent
-    mut bar_query: Query<(&mut Transform, &Parent), With<HealthBar>>,
+    mut bar_query: Query<(&mut Transform, &mut Sprite, &Parent), With<HealthBar>>,
     // On cherche la santé des Parents (les ennemis)
-    health_query: Query<&Health>,
+    health_query: Query<(&Health, Option<&StatusEffects>)>,
 ) {
-    for (mut transform, parent) in bar_query.iter_mut() {
+    for (mut transform, mut sprite, parent) in bar_query.iter_mut() {
         // On récupère la santé du parent via l'Entity stockée dans 'Parent'
-        if let Ok(health) = health_query.get(parent.get()) {
+        if let Ok((health, status)) = health_query.get(parent.get()) {
+            // La barre prend la couleur de l'effet actif le plus marquant
+            sprite.color = status.and_then(|status| status.get_tint()).unwrap_or(Color::RED);
+
             // Calcul du pourcentage
             let percent = health.current as f32 / health.max as f32;
             // On réduit l'échelle sur X (largeur)
@@ -182,17 +495,121 @@ fn update_health_bars(
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn enemy_death_system(
     mut commands: Commands, 
-    query: Query<(Entity, &Health)>,
+    mut query: Query<(Entity, &Health, &Transform, Option<&Bounty>, Option<&mut AnimationPlayer>), Without<Dying>>,
     mut stats: ResMut<PlayerStats>, // <--- Ajoutez ceci
+    difficulty: Res<Difficulty>,
+    mut money_events: EventWriter<MoneyChanged>,
 ) {
-    for (entity, health) in query.iter() {
+    for (entity, health, transform, bounty, animation) in query.iter_mut() {
         if health.current <= 0 {
-            // Gain d'argent (ex: 5 gold par orc)
-            // Idéalement, la récompense serait dans le composant Enemy
-            stats.money += 5; 
-            commands.entity(entity).despawn_recursive();
+            // Gain d'argent : la récompense dépend du type d'ennemi (composant Bounty)
+            let money = bounty.map_or(0, |bounty| difficulty.scale_bounty(bounty.0));
+            stats.money += money;
+            money_events.send(MoneyChanged { amount: money, position: Some(transform.translation.truncate()) });
+
+            // Animation de mort avant de disparaître (voir animation::play_animations)
+            match animation {
+                Some(mut animation) => {
+                    animation.play(AnimationState::Death);
+                    commands.entity(entity).remove::<Enemy>().insert(Dying).despawn_descendants();
+                }
+                None => commands.entity(entity).despawn_recursive(),
+            }
+        }
+    }
+}
+
+// Les ennemis qui viennent de mourir avec un SpawnOnDeath laissent leurs enfants
+// à leur position et à leur distance sur le chemin, un peu en arrière les uns des autres
+#[allow(clippy::type_complexity)]
+fn split_on_death(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    path: Res<Path>,
+    query: Query<(&Transform, &SpawnOnDeath, &DistanceTravelled, Option<&PathFollower>, Option<&FlightFollower>), Added<Dying>>,
+) {
+    for (transform, trigger, travelled, path_follower, flight_follower) in query.iter() {
+        let position = transform.translation.truncate();
+        // Un parent volant n'a pas de progression sur la route : ses enfants au sol partent
+        // du point de la route le plus proche
+        let parent_follower = path_follower.copied().or_else(|| get_nearest_follower(&path, position));
+        let hp = ((trigger.enemy.get_start_health() as f32 * trigger.scale) as i32).max(1);
+        let bounty = ((trigger.enemy.get_bounty() as f32 * trigger.scale).round() as i32).max(1);
+
+        for i in 0..trigger.count {
+            let path_follower = parent_follower.map(|follower| get_split_follower(&follower, i));
+            let Some(mut child) = spawn_enemy_following(&mut commands, &assets, &path, trigger.enemy, position, path_follower, flight_follower.copied()) else { continue; };
+            child.insert((
+                Health { current: hp, max: hp },
+                Bounty(bounty),
+                DistanceTravelled(travelled.0),
+                Sprite {
+                    custom_size: Some(Vec2::splat(TILE_SIZE * trigger.scale)),
+                    ..default()
+                },
+            ));
         }
     }
-}
\ No newline at end of file
+}
+
+// La rotation de l'ennemi s'applique aussi à ses enfants : on l'annule pour la barre de vie
+fn keep_health_bars_upright(
+    mut bar_query: Query<(&mut Transform, &Parent, &HealthBarAnchor)>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<HealthBarAnchor>)>,
+) {
+    for (mut transform, parent, anchor) in bar_query.iter_mut() {
+        let Ok(enemy_transform) = enemy_query.get(parent.get()) else { continue; };
+        let counter_rotation = enemy_transform.rotation.inverse();
+        transform.rotation = counter_rotation;
+        transform.translation = counter_rotation * anchor.offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::PathSegment;
+
+    // Une route droite de (0, 0) à (64, 0) puis une autre de (64, 0) à (64, 64)
+    fn get_test_path() -> Path {
+        Path {
+            segments: vec![
+                PathSegment::new(vec![Vec2::new(0.0, 0.0), Vec2::new(32.0, 0.0), Vec2::new(64.0, 0.0)], vec![1], 1.0),
+                PathSegment::new(vec![Vec2::new(64.0, 0.0), Vec2::new(64.0, 32.0), Vec2::new(64.0, 64.0)], vec![], 1.0),
+            ],
+            starts: vec![0],
+            flight_points: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn split_children_keep_parent_distance() {
+        let parent = PathFollower { segment: 1, distance: 50.0, branch: Some(2) };
+        for i in 0..4 {
+            let child = get_split_follower(&parent, i);
+            assert_eq!(child.segment, parent.segment);
+            assert_eq!(child.branch, parent.branch);
+            assert_eq!(child.distance, (parent.distance - i as f32 * SPLIT_SPACING).max(0.0));
+        }
+        // Jamais avant le début du tronçon
+        assert_eq!(get_split_follower(&parent, 10).distance, 0.0);
+    }
+
+    #[test]
+    fn flying_parent_children_land_on_nearest_road() {
+        let path = get_test_path();
+        // Au-dessus du milieu de la première route
+        let follower = get_nearest_follower(&path, Vec2::new(20.0, 30.0)).unwrap();
+        assert_eq!(follower.segment, 0);
+        assert!((follower.distance - 20.0).abs() < 1.0, "distance {}", follower.distance);
+        // À droite de la deuxième
+        let follower = get_nearest_follower(&path, Vec2::new(90.0, 48.0)).unwrap();
+        assert_eq!(follower.segment, 1);
+        assert!((follower.distance - 48.0).abs() < 1.0, "distance {}", follower.distance);
+
+        assert!(get_nearest_follower(&Path::default(), Vec2::ZERO).is_none());
+    }
+}
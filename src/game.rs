@@ -2,16 +2,20 @@ use bevy::{prelude::*, sprite::SpriteSheetBundle, window::PrimaryWindow, ui::nod
 use crate::{
     AppState, 
     GameAssets,
-    level,
+    level::GridLayout,
+    map::{CurrentMap, MapPlugin},
+    maze::MazeGrid,
     constants::tiles as TileTypes,
-    tower::{Tower, TowerType},
-    enemy::Enemy,
+    tower::{Tower, TowerType, Detector},
+    enemy::{Enemy, Flying},
+    stealth::Stealth,
+    wave::WaveManager,
     projectile::Projectile,
 };
 
 
 // Composant pour tout ce qui est DANS le jeu
-#[derive(Component)]
+#[derive(Component, Clone)]
 struct GameComponent;
 
 // Équivalent de TileType
@@ -24,18 +28,6 @@ pub enum TileType {
     End,
 }
 
-// Composant qui sera ajouté à chaque entité "Tuile"
-#[derive(Component)]
-pub struct GameTile {
-    pub tile_type: TileType,
-}
-
-// Ressource pour stocker les points de départ et de fin
-#[derive(Resource)]
-pub struct Path {
-    pub points: Vec<Vec2>, // Liste des points du chemin
-}
-
 // Ressource pour la tour sélectionnée dans le menu
 #[derive(Resource, Default)]
 struct SelectedTower {
@@ -48,6 +40,74 @@ pub struct PlayerStats {
     pub lives: i32,
 }
 
+// Chaque changement de PlayerStats est aussi envoyé en événement, pour que l'UI puisse l'animer.
+// `position` : où afficher le changement dans le monde (None si ce n'est lié à aucun endroit)
+#[derive(Event, Debug, Clone, Copy)]
+pub struct MoneyChanged {
+    pub amount: i32,
+    pub position: Option<Vec2>,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct LivesChanged {
+    pub amount: i32,
+    pub position: Option<Vec2>,
+}
+
+// Difficulté choisie sur l'écran de sélection : module les récompenses (Bounty)
+// et les vies perdues quand un ennemi arrive (LeakDamage)
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "FACILE",
+            Difficulty::Normal => "NORMAL",
+            Difficulty::Hard => "DIFFICILE",
+        }
+    }
+
+    pub fn next(&self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    pub fn get_bounty_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 0.75,
+        }
+    }
+
+    pub fn get_leak_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 2.0,
+        }
+    }
+
+    // Or gagné pour un ennemi de récompense `bounty`
+    pub fn scale_bounty(&self, bounty: i32) -> i32 {
+        (bounty as f32 * self.get_bounty_multiplier()).round() as i32
+    }
+
+    // Vies perdues pour un ennemi de `leak_damage` : toujours au moins une
+    pub fn scale_leak_damage(&self, leak_damage: i32) -> i32 {
+        ((leak_damage as f32 * self.get_leak_multiplier()).round() as i32).max(1)
+    }
+}
+
 // Pour les boutons de sélection de tours
 #[derive(Component)]
 struct TowerButton {
@@ -59,6 +119,26 @@ struct MoneyText;
 
 #[derive(Component)]
 struct LivesText;
+
+#[derive(Component)]
+struct WaveText;
+
+// Bouton "envoyer la vague" de la phase de construction
+#[derive(Component)]
+struct SendWaveButton;
+
+#[derive(Component)]
+struct SendWaveText;
+
+// "+5" ou "-1" qui monte et disparaît à l'endroit d'un gain d'or ou d'une perte de vie
+#[derive(Component)]
+struct StatPopup {
+    timer: Timer,
+}
+// Tirs des tours : ce qui décide des cibles possibles (détection des furtifs...) se place avant ce set
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TowerShootingSet;
+
 // Équivalent de "Playing.java"
 pub struct GamePlugin;
 
@@ -68,13 +148,22 @@ impl Plugin for GamePlugin {
             .init_resource::<SelectedTower>()
             // On initialise le joueur avec 100 Gold et 3 Vies
             .insert_resource(PlayerStats { money: 100, lives: 3 })
-            .add_systems(OnEnter(AppState::Playing), (setup_game, setup_game_ui))
+            .init_resource::<Difficulty>()
+            .add_event::<MoneyChanged>()
+            .add_event::<LivesChanged>()
+            // Carte du niveau sélectionné, nettoyée avec le reste du jeu
+            .add_plugins(MapPlugin { state: AppState::Playing, marker: GameComponent })
+            .add_systems(OnEnter(AppState::Playing), setup_game_ui)
             .add_systems(Update, (
                 tower_button_interaction, 
                 grid_click_interaction, 
-                tower_shooting,
-                update_ui_stats // <-- Système de mise à jour de l'UI
+                tower_shooting.in_set(TowerShootingSet),
+                send_wave_interaction,
+                update_ui_stats, // <-- Système de mise à jour de l'UI
+                update_send_wave_button,
             ).run_if(in_state(AppState::Playing).or_else(in_state(AppState::Simulation))))
+            // Pas d'animation en simulation : trop d'ennemis
+            .add_systems(Update, (spawn_stat_popups, animate_stat_popups).run_if(in_state(AppState::Playing)))
             .add_systems(OnExit(AppState::Playing), cleanup_game);
     }
 }
@@ -83,187 +172,9 @@ pub fn get_atlas_index(x: usize, y: usize) -> usize {
     y * 10 + x
 }
 
-// équivalent constructeur Playing()
-fn setup_game(
-    mut commands: Commands,
-    assets: Res<GameAssets>,
-) {
+fn setup_game_ui(mut commands: Commands, assets: Res<GameAssets>) {
     println!("Lancement du jeu (Playing) !");
 
-    // --- 1. Initialisation et Constantes ---
-    let level_data = level::get_level_data();
-    
-    // Positions temporaires pour le pathfinding
-    let mut start_pos = Vec2::ZERO;
-    
-    // Configuration de la grille
-    const TILE_SIZE: f32 = 32.0;
-    const MAP_WIDTH: f32 = 20.0 * TILE_SIZE;
-    const MAP_HEIGHT: f32 = 20.0 * TILE_SIZE;
-    
-    // --- CORRECTION 1 : DÉCALAGE DE LA CARTE ---
-    // Fenêtre = 740px, Map = 640px. UI = 100px.
-    // Si on centre (0,0), la map va de -320 à +320.
-    // L'UI va de -370 à -270. Il y a 50px de chevauchement.
-    // On déplace tout le monde vers le HAUT de 50px.
-    let vertical_shift = 50.0;
-
-    let x_offset = -MAP_WIDTH / 2.0 + TILE_SIZE / 2.0;
-    // On ajoute le vertical_shift ici
-    let y_offset = (MAP_HEIGHT / 2.0 - TILE_SIZE / 2.0) + vertical_shift;
-
-    // --- 2. Génération Visuelle de la Carte ---
-    for (y, row) in level_data.iter().enumerate() {
-        for (x, &tile_id) in row.iter().enumerate() {
-            
-            // Position dans le monde
-            let pos = Vec2::new(
-                x_offset + x as f32 * TILE_SIZE,
-                y_offset - y as f32 * TILE_SIZE,
-            );
-            
-            let tile_type = get_tile_type(tile_id);
-            
-            let mut rotation = Quat::IDENTITY; 
-            let mut index = 0; 
-
-            // Logique d'affichage (Match complet avec rotations corrigées)
-            match tile_id {
-                // --- Tuiles Composites (Eau + Sable/Terre) ---
-                8 => spawn_composite_tile(&mut commands, &assets, pos, get_atlas_index(0, 0), get_atlas_index(5, 0), Quat::IDENTITY),
-                9 => spawn_composite_tile(&mut commands, &assets, pos, get_atlas_index(0, 0), get_atlas_index(5, 0), Quat::from_rotation_z(-90.0f32.to_radians())),
-                10 => spawn_composite_tile(&mut commands, &assets, pos, get_atlas_index(0, 0), get_atlas_index(5, 0), Quat::from_rotation_z(180.0f32.to_radians())),
-                11 => spawn_composite_tile(&mut commands, &assets, pos, get_atlas_index(0, 0), get_atlas_index(5, 0), Quat::from_rotation_z(90.0f32.to_radians())),
-                
-                12 => spawn_composite_tile(&mut commands, &assets, pos, get_atlas_index(0, 0), get_atlas_index(6, 0), Quat::IDENTITY),
-                13 => spawn_composite_tile(&mut commands, &assets, pos, get_atlas_index(0, 0), get_atlas_index(6, 0), Quat::from_rotation_z(-90.0f32.to_radians())),
-                14 => spawn_composite_tile(&mut commands, &assets, pos, get_atlas_index(0, 0), get_atlas_index(6, 0), Quat::from_rotation_z(180.0f32.to_radians())),
-                15 => spawn_composite_tile(&mut commands, &assets, pos, get_atlas_index(0, 0), get_atlas_index(6, 0), Quat::from_rotation_z(90.0f32.to_radians())),
-
-                16 => spawn_composite_tile(&mut commands, &assets, pos, get_atlas_index(0, 0), get_atlas_index(4, 0), Quat::IDENTITY),
-                17 => spawn_composite_tile(&mut commands, &assets, pos, get_atlas_index(0, 0), get_atlas_index(4, 0), Quat::from_rotation_z(-90.0f32.to_radians())),
-                18 => spawn_composite_tile(&mut commands, &assets, pos, get_atlas_index(0, 0), get_atlas_index(4, 0), Quat::from_rotation_z(180.0f32.to_radians())),
-                19 => spawn_composite_tile(&mut commands, &assets, pos, get_atlas_index(0, 0), get_atlas_index(4, 0), Quat::from_rotation_z(90.0f32.to_radians())),
-
-                // --- Tuiles Simples ---
-                _ => {
-                    (index, rotation) = match tile_id {
-                        0 => (get_atlas_index(9, 0), Quat::IDENTITY),
-                        1 => (get_atlas_index(0, 0), Quat::IDENTITY),
-                        2 => (get_atlas_index(8, 0), Quat::IDENTITY),
-                        3 => (get_atlas_index(8, 0), Quat::from_rotation_z(-90.0f32.to_radians())),
-                        4 => (get_atlas_index(7, 0), Quat::IDENTITY),
-                        5 => (get_atlas_index(7, 0), Quat::from_rotation_z(-90.0f32.to_radians())),
-                        6 => (get_atlas_index(7, 0), Quat::from_rotation_z(180.0f32.to_radians())),
-                        7 => (get_atlas_index(7, 0), Quat::from_rotation_z(90.0f32.to_radians())),
-
-                        20 => { // START
-                            spawn_composite_tile(&mut commands, &assets, pos, get_atlas_index(8, 0), get_atlas_index(7, 2), Quat::IDENTITY);
-                            (999, Quat::IDENTITY) // 999 = Ne pas spawner de tuile simple supplémentaire
-                        },
-                        21 => { // END
-                            spawn_composite_tile(&mut commands, &assets, pos, get_atlas_index(8, 0), get_atlas_index(8, 2), Quat::IDENTITY);
-                            (999, Quat::IDENTITY)
-                        },
-                        _ => (get_atlas_index(0, 0), Quat::IDENTITY),
-                    };
-
-                    if index != 999 {
-                        commands.spawn((
-                            SpriteSheetBundle {
-                                texture: assets.sprite_atlas.clone(),
-                                atlas: TextureAtlas {
-                                    layout: assets.sprite_atlas_layout.clone(),
-                                    index,
-                                },
-                                transform: Transform {
-                                    translation: pos.extend(0.0),
-                                    rotation,
-                                    ..default()
-                                },
-                                ..default()
-                            },
-                            GameTile { tile_type },
-                            GameComponent,
-                            Name::new(format!("Tile ({x},{y})")),
-                        ));
-                    }
-                }
-            }
-
-            // On capture la position de départ pour le pathfinding
-            if tile_type == TileType::Start {
-                start_pos = pos;
-            }
-        }
-    }
-
-    // --- 3. Calcul du Chemin (Pathfinding) ---
-    // On construit la liste des points que l'ennemi devra suivre
-    
-    let mut path_points = Vec::new();
-    path_points.push(start_pos);
-
-    // On convertit la position de départ (pixels) en coordonnées de grille (0-19)
-    let mut grid_x = ((start_pos.x - x_offset) / TILE_SIZE).round() as i32;
-    let mut grid_y = ((y_offset - start_pos.y) / TILE_SIZE).round() as i32;
-    
-    let mut last_grid_pos = (grid_x, grid_y); // Pour ne pas revenir en arrière
-
-    // Boucle de recherche de chemin
-    for _ in 0..100 { // Sécurité anti-boucle infinie
-        let mut found_next = false;
-        let neighbors = [(0, -1), (0, 1), (-1, 0), (1, 0)]; // Haut, Bas, Gauche, Droite
-
-        for (dx, dy) in neighbors {
-            let nx = grid_x + dx;
-            let ny = grid_y + dy;
-
-            // Vérifications limites + on évite le retour arrière
-            if nx < 0 || ny < 0 || nx >= 20 || ny >= 20 { continue; }
-            if (nx, ny) == last_grid_pos { continue; }
-
-            let tile_id = level_data[ny as usize][nx as usize];
-            let tile_type = get_tile_type(tile_id);
-
-            // Si c'est une route ou la fin, c'est le bon chemin
-            if tile_type == TileType::Road || tile_type == TileType::End {
-                last_grid_pos = (grid_x, grid_y);
-                grid_x = nx;
-                grid_y = ny;
-
-                let next_world_pos = Vec2::new(
-                    x_offset + nx as f32 * TILE_SIZE,
-                    y_offset - ny as f32 * TILE_SIZE,
-                );
-                path_points.push(next_world_pos);
-                found_next = true;
-                
-                if tile_type == TileType::End {
-                    break; // On a trouvé la fin, on sort du 'for neighbors'
-                }
-                break; // On a trouvé le prochain pas, on passe à l'itération suivante
-            }
-        }
-
-        if !found_next { break; } // Plus de route trouvée ou cul de sac
-        
-        // Si on est sur la tuile de fin, on arrête tout
-        let current_tile_id = level_data[grid_y as usize][grid_x as usize];
-        if get_tile_type(current_tile_id) == TileType::End {
-            break;
-        }
-    }
-
-    println!("Chemin calculé avec succès : {} points", path_points.len());
-
-    // On insère la Ressource pour que le système d'Ennemis puisse la lire
-    commands.insert_resource(Path {
-        points: path_points,
-    });
-}
-
-fn setup_game_ui(mut commands: Commands, assets: Res<GameAssets>) {
     // --- CORRECTION 2 : COULEUR EXACTE ---
     // Java: new Color(220, 123, 15)
     let bar_color = Color::rgb_u8(220, 123, 15);
@@ -288,6 +199,7 @@ fn setup_game_ui(mut commands: Commands, assets: Res<GameAssets>) {
         spawn_tower_button(parent, &assets, TowerType::Canon);
         spawn_tower_button(parent, &assets, TowerType::Archer);
         spawn_tower_button(parent, &assets, TowerType::Wizard);
+        spawn_send_wave_button(parent);
     });
 
     commands.spawn((
@@ -329,6 +241,19 @@ fn setup_game_ui(mut commands: Commands, assets: Res<GameAssets>) {
             ),
             LivesText, // Marqueur
         ));
+
+        // Texte Vague
+        parent.spawn((
+            TextBundle::from_section(
+                "Wave: -",
+                TextStyle {
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ),
+            WaveText, // Marqueur
+        ));
     });
 }
 
@@ -372,19 +297,38 @@ fn spawn_tower_button(parent: &mut ChildBuilder, assets: &Res<GameAssets>, tower
     });
 }
 
-pub fn spawn_composite_tile(commands: &mut Commands, assets: &Res<GameAssets>, pos: Vec2, base_i: usize, over_i: usize, rot: Quat) {
-     commands.spawn((
-        SpriteSheetBundle {
-            texture: assets.sprite_atlas.clone(),
-            atlas: TextureAtlas { layout: assets.sprite_atlas_layout.clone(), index: base_i },
-            transform: Transform::from_xyz(pos.x, pos.y, 0.0), ..default()
-        }, GameTile { tile_type: TileType::Water }, GameComponent));
-    commands.spawn((
-        SpriteSheetBundle {
-            texture: assets.sprite_atlas.clone(),
-            atlas: TextureAtlas { layout: assets.sprite_atlas_layout.clone(), index: over_i },
-            transform: Transform { translation: pos.extend(0.1), rotation: rot, ..default() }, ..default()
-        }, GameTile { tile_type: TileType::Water }, GameComponent));
+// Visible seulement pendant la phase de construction (voir update_send_wave_button)
+fn spawn_send_wave_button(parent: &mut ChildBuilder) {
+    parent.spawn((
+        ButtonBundle {
+            style: Style {
+                height: Val::Px(50.0),
+                margin: UiRect::left(Val::Px(40.0)),
+                padding: UiRect::horizontal(Val::Px(10.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                border: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            background_color: Color::GRAY.into(),
+            border_color: BorderColor(Color::BLACK),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        SendWaveButton,
+    )).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section(
+                "Send next wave",
+                TextStyle {
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ),
+            SendWaveText, // Marqueur
+        ));
+    });
 }
 
 // Gère le clic sur les boutons du bas
@@ -430,10 +374,13 @@ fn tower_button_interaction(
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn update_ui_stats(
     stats: Res<PlayerStats>,
-    mut money_query: Query<&mut Text, (With<MoneyText>, Without<LivesText>)>,
-    mut lives_query: Query<&mut Text, (With<LivesText>, Without<MoneyText>)>,
+    waves: Option<Res<WaveManager>>,
+    mut money_query: Query<&mut Text, (With<MoneyText>, Without<LivesText>, Without<WaveText>)>,
+    mut lives_query: Query<&mut Text, (With<LivesText>, Without<MoneyText>, Without<WaveText>)>,
+    mut wave_query: Query<&mut Text, (With<WaveText>, Without<MoneyText>, Without<LivesText>)>,
 ) {
     // On met à jour le texte seulement si la ressource a changé
     // (Bevy le gère, mais ici on réécrit à chaque frame par simplicité)
@@ -443,9 +390,113 @@ fn update_ui_stats(
     for mut text in lives_query.iter_mut() {
         text.sections[0].value = format!("Lives: {}", stats.lives);
     }
+    if let Some(waves) = waves {
+        let label = if waves.is_finished() {
+            "Victoire !".to_string()
+        } else if waves.is_build_phase() {
+            // Compte à rebours de la phase de construction
+            format!("Wave: {}/{} dans {}s", waves.current + 1, waves.waves.len(), waves.get_build_time_left().ceil())
+        } else {
+            format!("Wave: {}/{}", waves.current + 1, waves.waves.len())
+        };
+        for mut text in wave_query.iter_mut() {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+// Lance la vague en avance : le temps de construction sauté rapporte de l'or
+#[allow(clippy::type_complexity)]
+fn send_wave_interaction(
+    mut interaction_query: Query<(&Interaction, &mut BorderColor), (Changed<Interaction>, With<SendWaveButton>)>,
+    waves: Option<ResMut<WaveManager>>,
+    mut stats: ResMut<PlayerStats>,
+    mut money_events: EventWriter<MoneyChanged>,
+) {
+    let Some(mut waves) = waves else { return; };
+    for (interaction, mut border_color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                if !waves.is_build_phase() { continue; }
+                let bonus = waves.call_early();
+                stats.money += bonus;
+                money_events.send(MoneyChanged { amount: bonus, position: None });
+                println!("Vague {} lancée en avance : +{} Gold", waves.current + 1, bonus);
+            }
+            Interaction::Hovered => *border_color = BorderColor(Color::WHITE),
+            Interaction::None => *border_color = BorderColor(Color::BLACK),
+        }
+    }
+}
+
+// Affiche le bouton pendant la phase de construction, avec le bonus en cours
+fn update_send_wave_button(
+    waves: Option<Res<WaveManager>>,
+    mut button_query: Query<&mut Visibility, With<SendWaveButton>>,
+    mut text_query: Query<&mut Text, With<SendWaveText>>,
+) {
+    let build_phase = waves.as_ref().is_some_and(|waves| waves.is_build_phase());
+    for mut visibility in button_query.iter_mut() {
+        *visibility = if build_phase { Visibility::Inherited } else { Visibility::Hidden };
+    }
+    let Some(waves) = waves else { return; };
+    if !build_phase { return; }
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format!("Send next wave (+{} Gold)", waves.get_early_call_bonus());
+    }
+}
+
+fn spawn_stat_popups(
+    mut commands: Commands,
+    mut money_events: EventReader<MoneyChanged>,
+    mut lives_events: EventReader<LivesChanged>,
+) {
+    let popups = money_events.read().map(|event| (event.amount, event.position, Color::GOLD))
+        .chain(lives_events.read().map(|event| (event.amount, event.position, Color::RED)));
+
+    for (amount, position, color) in popups {
+        let Some(position) = position else { continue; };
+        if amount == 0 { continue; }
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("{amount:+}"),
+                    TextStyle {
+                        font_size: 20.0,
+                        color,
+                        ..default()
+                    },
+                ),
+                transform: Transform::from_xyz(position.x, position.y + 16.0, 5.0),
+                ..default()
+            },
+            StatPopup { timer: Timer::from_seconds(1.0, TimerMode::Once) },
+            GameComponent,
+        ));
+    }
+}
+
+// Les popups montent en s'effaçant puis disparaissent
+fn animate_stat_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut Text, &mut StatPopup)>,
+) {
+    for (entity, mut transform, mut text, mut popup) in query.iter_mut() {
+        popup.timer.tick(time.delta());
+        if popup.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y += 30.0 * time.delta_seconds();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(popup.timer.fraction_remaining());
+        }
+    }
 }
 
 // Gère le clic sur la grille pour poser une tour
+#[allow(clippy::too_many_arguments)]
 fn grid_click_interaction(
     mut commands: Commands,
     mouse_input: Res<ButtonInput<MouseButton>>,
@@ -454,7 +505,12 @@ fn grid_click_interaction(
     selected_tower: Res<SelectedTower>,
     assets: Res<GameAssets>,
     mut stats: ResMut<PlayerStats>, // NOUVEAU : On a besoin de l'argent
+    mut money_events: EventWriter<MoneyChanged>,
+    map: Option<Res<CurrentMap>>,
+    layout: Option<Res<GridLayout>>,
+    mut maze: Option<ResMut<MazeGrid>>,
 ) {
+    let (Some(map), Some(layout)) = (map, layout) else { return; };
     if mouse_input.just_pressed(MouseButton::Left) {
         let Some(tower_type) = selected_tower.tower_type else { return; };
         
@@ -467,49 +523,52 @@ fn grid_click_interaction(
 
         let (camera, camera_transform) = camera_q.single();
         let Some(window) = windows.get_single().ok() else { return };
-
-        if let Some(world_position) = window.cursor_position()
-            .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
-            .map(|ray| ray.origin.truncate())
-        {
-            // Constantes identiques à setup_game
-            const TILE_SIZE: f32 = 32.0;
-            const MAP_WIDTH: f32 = 20.0 * TILE_SIZE;
-            const MAP_HEIGHT: f32 = 20.0 * TILE_SIZE;
-            let vertical_shift = 50.0; 
-            let x_offset = -MAP_WIDTH / 2.0 + TILE_SIZE / 2.0;
-            let y_offset = (MAP_HEIGHT / 2.0 - TILE_SIZE / 2.0) + vertical_shift;
-
-            let grid_x = ((world_position.x - x_offset) / TILE_SIZE).round();
-            let grid_y = ((y_offset - world_position.y) / TILE_SIZE).round();
-
-            if grid_x >= 0.0 && grid_x < 20.0 && grid_y >= 0.0 && grid_y < 20.0 {
-                if world_position.y < (-370.0 + 100.0) { return; }
-
-                let ix = grid_x as usize;
-                let iy = grid_y as usize;
-                let level_data = level::get_level_data();
-                
-                if level_data[iy][ix] == 0 { 
-                    let snap_pos = Vec2::new(x_offset + grid_x * TILE_SIZE, y_offset - grid_y * TILE_SIZE);
-                    let (range, damage, cooldown) = tower_type.get_base_stats();
-
-                    commands.spawn((
-                        SpriteSheetBundle {
-                            texture: assets.sprite_atlas.clone(),
-                            atlas: TextureAtlas { layout: assets.sprite_atlas_layout.clone(), index: tower_type.get_sprite_index() },
-                            transform: Transform::from_xyz(snap_pos.x, snap_pos.y, 2.0), 
-                            ..default()
-                        },
-                        Tower { range, damage, cooldown: Timer::from_seconds(cooldown, TimerMode::Repeating) },
-                        GameComponent,
-                    ));
-
-                    // 2. Payer la tour
-                    stats.money -= cost;
-                    println!("Tour achetée ! Reste : {}", stats.money);
+        // Clic sur la barre d'UI ou hors de la carte : ce n'est pas pour la grille
+        let Some((ix, iy)) = layout.cursor_to_grid(window, camera, camera_transform) else { return };
+
+        let buildable = match maze.as_mut() {
+            // Mode labyrinthe : herbe ou route, sans jamais couper la route des ennemis
+            Some(maze) => {
+                let allowed = maze.can_build(ix, iy);
+                if allowed {
+                    maze.add_tower(ix, iy);
+                } else {
+                    println!("Impossible de construire en ({ix}, {iy}) : case occupée ou route bloquée");
                 }
+                allowed
+            }
+            None => map.get_tile(ix, iy) == Some(0),
+        };
+
+        if buildable {
+            let snap_pos = layout.grid_to_world(ix, iy);
+            let (range, damage, cooldown) = tower_type.get_base_stats();
+
+            let mut tower = commands.spawn((
+                SpriteSheetBundle {
+                    texture: assets.sprite_atlas.clone(),
+                    atlas: TextureAtlas { layout: assets.sprite_atlas_layout.clone(), index: tower_type.get_sprite_index() },
+                    transform: Transform::from_xyz(snap_pos.x, snap_pos.y, 2.0), 
+                    ..default()
+                },
+                Tower {
+                    range,
+                    damage,
+                    cooldown: Timer::from_seconds(cooldown, TimerMode::Repeating),
+                    targets: tower_type.get_targets(),
+                    damage_type: tower_type.get_damage_type(),
+                    on_hit: tower_type.get_on_hit_effects(),
+                },
+                GameComponent,
+            ));
+            if tower_type.is_detector() {
+                tower.insert(Detector { range });
             }
+
+            // 2. Payer la tour
+            stats.money -= cost;
+            money_events.send(MoneyChanged { amount: -cost, position: Some(snap_pos) });
+            println!("Tour achetée ! Reste : {}", stats.money);
         }
     }
 }
@@ -540,18 +599,18 @@ fn cleanup_game(mut commands: Commands, query: Query<Entity, With<GameComponent>
     for entity in query.iter() {
         commands.entity(entity).despawn_recursive();
     }
-    // On retire aussi la ressource Path et SelectedTower
-    commands.remove_resource::<Path>();
+    // On retire aussi SelectedTower (la carte et le Path sont retirés par MapPlugin)
     commands.remove_resource::<SelectedTower>();
     commands.remove_resource::<PlayerStats>();
 }
 
+#[allow(clippy::type_complexity)]
 fn tower_shooting(
     mut commands: Commands,
     assets: Res<GameAssets>,
     time: Res<Time>,
     mut tower_query: Query<(&Transform, &mut Tower)>, // Les tours
-    enemy_query: Query<(Entity, &Transform), With<Enemy>>, // Les ennemis
+    enemy_query: Query<(Entity, &Transform, Has<Flying>, Option<&Stealth>), With<Enemy>>, // Les ennemis
 ) {
     for (tower_transform, mut tower) in tower_query.iter_mut() {
         // Avancer le cooldown de la tour
@@ -566,7 +625,12 @@ fn tower_shooting(
 
             let tower_pos = tower_transform.translation.truncate();
 
-            for (enemy_entity, enemy_transform) in enemy_query.iter() {
+            for (enemy_entity, enemy_transform, flying, stealth) in enemy_query.iter() {
+                // Certaines tours ne peuvent pas viser les ennemis volants
+                if !tower.targets.can_target(flying) { continue; }
+                // Ennemi furtif : invisible tant qu'aucun détecteur ne le repère
+                if stealth.is_some_and(|stealth| !stealth.revealed) { continue; }
+
                 let enemy_pos = enemy_transform.translation.truncate();
                 let dist_sq = tower_pos.distance_squared(enemy_pos);
 
@@ -595,6 +659,8 @@ fn tower_shooting(
                     Projectile {
                         target,
                         damage: tower.damage,
+                        damage_type: tower.damage_type,
+                        effects: tower.on_hit.clone(),
                         speed: 300.0, // Rapide !
                     },
                     GameComponent, // Pour le nettoyage
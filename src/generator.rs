@@ -0,0 +1,198 @@
+use crate::{
+    autotile::autotile,
+    constants::tiles as Terrain,
+    level::{self, Level},
+};
+
+// Générateur pseudo-aléatoire minimal (SplitMix64) : même graine = même suite de nombres,
+// sur toutes les plateformes, ce qui permet de rejouer exactement une carte générée.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Entier dans [min, max] (bornes incluses)
+    pub fn range(&mut self, min: usize, max: usize) -> usize {
+        if max <= min { return min; }
+        min + (self.next_u64() % (max - min + 1) as u64) as usize
+    }
+
+    // Flottant dans [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+// Réglages du générateur
+#[derive(Debug, Clone)]
+pub struct GeneratorParams {
+    // Longueur min/max d'un tronçon horizontal de route
+    pub min_straight: usize,
+    pub max_straight: usize,
+    // Distance (en tuiles) autour de la route où l'on met de l'herbe constructible
+    pub build_radius: usize,
+}
+
+impl Default for GeneratorParams {
+    fn default() -> Self {
+        GeneratorParams { min_straight: 2, max_straight: 5, build_radius: 2 }
+    }
+}
+
+// Génère un niveau jouable : une route sinueuse du bord gauche (départ) au bord droit (arrivée),
+// de l'herbe constructible le long de la route et de l'eau partout ailleurs.
+//
+// La route avance toujours vers la droite par tronçons horizontaux d'au moins 2 tuiles,
+// séparés par des tronçons verticaux : deux morceaux de route non consécutifs ne se touchent
+// jamais, donc le graphe du chemin est un seul tronçon qui suit exactement la route générée.
+pub fn generate_level(seed: u64, width: usize, height: usize, params: &GeneratorParams) -> Level {
+    let width = width.max(3);
+    let height = height.max(3);
+    let min_straight = params.min_straight.max(2);
+    let max_straight = params.max_straight.max(min_straight);
+    let mut rng = SeededRng::new(seed);
+
+    let mut terrain = vec![vec![Terrain::WATER_TILE; width]; height];
+    let mut road: Vec<(usize, usize)> = Vec::new();
+
+    let mut x = 0;
+    let mut y = rng.range(1, height - 2);
+    road.push((x, y));
+
+    loop {
+        // Tronçon horizontal
+        let length = rng.range(min_straight, max_straight).min(width - 1 - x);
+        for _ in 0..length {
+            x += 1;
+            road.push((x, y));
+        }
+        if x == width - 1 { break; }
+
+        // Tronçon vertical vers une nouvelle ligne
+        let mut target_y = rng.range(1, height - 2);
+        if target_y == y {
+            target_y = if y > 1 { y - 1 } else { y + 1 };
+        }
+        while y != target_y {
+            y = if target_y > y { y + 1 } else { y - 1 };
+            road.push((x, y));
+        }
+    }
+
+    // Herbe autour de la route
+    let radius = params.build_radius as i32;
+    for &(road_x, road_y) in road.iter() {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let nx = road_x as i32 + dx;
+                let ny = road_y as i32 + dy;
+                if nx < 0 || ny < 0 || nx >= width as i32 || ny >= height as i32 { continue; }
+                terrain[ny as usize][nx as usize] = Terrain::GRASS_TILE;
+            }
+        }
+    }
+
+    fill_water_slivers(&mut terrain);
+
+    for &(road_x, road_y) in road.iter() {
+        terrain[road_y][road_x] = Terrain::ROAD_TILE;
+    }
+    let (start_x, start_y) = road[0];
+    let (end_x, end_y) = road[road.len() - 1];
+    terrain[start_y][start_x] = Terrain::START_TILE;
+    terrain[end_y][end_x] = Terrain::END_TILE;
+
+    let level = Level::new(format!("Aléatoire #{seed}"), autotile(&terrain));
+    debug_assert!(level::validate_level(&level).is_ok(), "carte générée invalide (graine {seed})");
+    level
+}
+
+// L'atlas n'a pas de sprite pour une bande d'eau entre deux rives :
+// on remplit d'herbe l'eau qui a de la terre des deux côtés opposés
+fn fill_water_slivers(terrain: &mut [Vec<u32>]) {
+    let height = terrain.len();
+    let width = terrain[0].len();
+    let is_land = |terrain: &[Vec<u32>], x: i32, y: i32| {
+        x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height
+            && terrain[y as usize][x as usize] != Terrain::WATER_TILE
+    };
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for y in 0..height {
+            for x in 0..width {
+                if terrain[y][x] != Terrain::WATER_TILE { continue; }
+                let (xi, yi) = (x as i32, y as i32);
+                let horizontal = is_land(terrain, xi - 1, yi) && is_land(terrain, xi + 1, yi);
+                let vertical = is_land(terrain, xi, yi - 1) && is_land(terrain, xi, yi + 1);
+                if horizontal || vertical {
+                    terrain[y][x] = Terrain::GRASS_TILE;
+                    changed = true;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{get_tile_type, TileType};
+
+    const SEEDS: [u64; 6] = [0, 1, 42, 1234, 987_654_321, u64::MAX];
+    // Hauteur minimale (3) comprise
+    const SIZES: [(usize, usize); 5] = [(3, 3), (10, 3), (20, 20), (12, 30), (40, 8)];
+
+    #[test]
+    fn same_seed_same_level() {
+        let params = GeneratorParams::default();
+        for seed in SEEDS {
+            for (width, height) in SIZES {
+                assert_eq!(generate_level(seed, width, height, &params), generate_level(seed, width, height, &params));
+            }
+        }
+    }
+
+    #[test]
+    fn generated_levels_are_valid() {
+        let params = GeneratorParams::default();
+        for seed in SEEDS {
+            for (width, height) in SIZES {
+                let level = generate_level(seed, width, height, &params);
+                assert!(level::validate_level(&level).is_ok(), "graine {seed}, {width}x{height}");
+            }
+        }
+    }
+
+    #[test]
+    fn generated_tiles_are_known() {
+        let params = GeneratorParams::default();
+        for seed in SEEDS {
+            for (width, height) in SIZES {
+                let level = generate_level(seed, width, height, &params);
+                for &tile_id in level.tiles.iter().flatten() {
+                    // get_tile_type range tout id inconnu dans l'eau : seuls 1 et 8..=19 sont de l'eau
+                    let known = match get_tile_type(tile_id) {
+                        TileType::Water => tile_id == 1 || (8..=19).contains(&tile_id),
+                        _ => true,
+                    };
+                    assert!(known, "tuile inconnue {tile_id} (graine {seed}, {width}x{height})");
+                }
+            }
+        }
+    }
+}
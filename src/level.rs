@@ -1,58 +1,695 @@
 #![allow(dead_code)]
 
+use bevy::{
+    prelude::*,
+    window::PrimaryWindow,
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadedFolder, RecursiveDependencyLoadState},
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use std::collections::{HashMap, HashSet};
+use crate::{
+    AppState,
+    autotile::autotile,
+    tiled::TiledLoader,
+    constants::tiles as TerrainTypes,
+    game::{get_tile_type, TileType},
+    wave::WaveDefinition,
+};
+
 // Équivalent de PathPoint.java
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct PathPoint {
     pub x: usize,
     pub y: usize,
 }
 
-// Le tableau de 20x20 de LevelBuild.java
-const LEVEL_DATA: [[u32; 20]; 20] = [
-    [ 0, 0, 0, 0, 15, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1 ],
-    [ 0, 0, 0, 0, 15, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1 ],
-    [ 0, 0, 0, 0, 15, 1, 1, 1, 18, 14, 14, 14, 19, 1, 1, 1, 1, 18, 14, 14 ],
-    [ 0, 0, 0, 0, 15, 1, 1, 1, 13, 0, 0, 0, 15, 1, 1, 1, 1, 13, 4, 21],
-    [ 0, 0, 0, 0, 15, 1, 1, 1, 13, 0, 0, 0, 15, 1, 1, 1, 1, 13, 3, 0 ],
-    [ 0, 0, 0, 0, 15, 1, 1, 1, 13, 0, 0, 0, 15, 1, 1, 1, 1, 13, 3, 0 ],
-    [ 0, 0, 0, 0, 15, 1, 1, 1, 17, 12, 12, 12, 16, 1, 1, 1, 1, 13, 3, 0 ],
-    [ 0, 0, 0, 0, 8, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 11, 3, 0 ],
-    [ 0, 0, 4, 2, 2, 2, 2, 5, 0, 0, 0, 4, 2, 2, 2, 2, 2, 2, 6, 0 ],
-    [ 0, 0, 3, 9, 12, 12,10, 3, 0, 0, 0, 3, 9, 12, 12, 12, 12, 10, 0, 0 ],
-    [ 0, 0, 3, 15, 1, 1, 13, 3, 0, 0, 0, 3, 15, 1, 1, 1, 1, 13, 0, 9 ],
-    [ 0, 0, 3, 15, 1, 1, 13, 3, 0, 0, 0, 3, 15, 1, 1, 18, 14, 11, 0, 15 ],
-    [ 0, 0, 3, 15, 1, 1, 13, 7, 2, 2, 2, 6, 15, 1, 1, 13, 0, 0, 0, 15],
-    [ 0, 0, 3, 15, 1, 1, 17, 12, 12, 12, 12, 12, 16, 1, 1, 13, 0, 0, 9, 16],
-    [ 0, 0, 3, 15, 1, 1, 1, 18, 14, 14, 14, 19, 1, 1, 1, 13, 0, 0, 15, 1],
-    [ 0, 0, 3, 15, 1, 1, 1, 13, 0, 0, 0, 15, 1, 1, 1, 13, 0, 0, 15, 1],
-    [ 0, 0, 3, 15, 1, 1, 18, 11, 0, 0, 0, 8, 19, 1, 1, 13, 0, 0, 15, 1],
-    [ 0, 0, 3, 15, 1, 1, 13, 0, 0, 0, 0, 0, 15, 1, 1, 13, 0, 0, 15, 1],
-    [ 0, 0, 3, 15, 1, 1, 17, 12, 12, 12, 12, 12, 16, 1, 1, 13, 0, 0, 15, 1],
-    [ 20, 2, 6, 8, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 11, 0, 0, 8, 14]
-];
-
-pub fn get_level_data() -> &'static [[u32; 20]; 20] {
-    &LEVEL_DATA
-}
-
-pub fn get_start_point() -> PathPoint {
-    for (y, row) in LEVEL_DATA.iter().enumerate() {
+// Un niveau chargé depuis un fichier "assets/levels/*.level.ron"
+// (remplace l'ancien tableau LEVEL_DATA de LevelBuild.java)
+#[derive(Asset, TypePath, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Level {
+    pub name: String,
+    #[serde(default)]
+    pub tiles: Vec<Vec<u32>>, // tiles[y][x] = id de la tuile
+    // Variante plus simple à écrire à la main : terrain sémantique (constants::tiles)
+    // converti en tuiles par l'autotiling au chargement
+    #[serde(default, skip_serializing)]
+    pub terrain: Option<Vec<Vec<u32>>>,
+    // Poids des branches aux embranchements (1.0 par défaut)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub branch_weights: Vec<BranchWeight>,
+    // Points de passage (x, y) des ennemis volants avant l'arrivée (vide = tout droit)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flight_path: Vec<(usize, usize)>,
+    // Cases de route à la vitesse modifiée (boue, pont, accélérateur)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<TerrainCell>,
+    // Vagues d'ennemis (vide = wave::default_waves)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub waves: Vec<WaveDefinition>,
+}
+
+// Poids de la branche qui commence sur la case (x, y), juste après un carrefour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BranchWeight {
+    pub x: usize,
+    pub y: usize,
+    pub weight: f32,
+}
+
+// Propriété de terrain d'une case de route : change la vitesse des ennemis au sol qui la traversent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TerrainModifier {
+    Mud,    // Boue : les ennemis s'enlisent
+    Bridge, // Pont étroit : les ennemis ralentissent un peu
+    Boost,  // Route rapide : les ennemis accélèrent
+}
+
+impl TerrainModifier {
+    pub const ALL: [TerrainModifier; 3] = [TerrainModifier::Mud, TerrainModifier::Bridge, TerrainModifier::Boost];
+
+    pub fn get_speed_multiplier(&self) -> f32 {
+        match self {
+            TerrainModifier::Mud => 0.5,
+            TerrainModifier::Bridge => 0.8,
+            TerrainModifier::Boost => 1.5,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            TerrainModifier::Mud => "mud",
+            TerrainModifier::Bridge => "bridge",
+            TerrainModifier::Boost => "boost",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<TerrainModifier> {
+        TerrainModifier::ALL.into_iter().find(|modifier| modifier.get_name() == name)
+    }
+
+    // Teinte posée sur la tuile (l'atlas n'a pas de sprite dédié)
+    pub fn get_color(&self) -> Color {
+        match self {
+            TerrainModifier::Mud => Color::rgba(0.35, 0.2, 0.05, 0.6),
+            TerrainModifier::Bridge => Color::rgba(0.6, 0.4, 0.2, 0.5),
+            TerrainModifier::Boost => Color::rgba(0.2, 0.6, 1.0, 0.45),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TerrainCell {
+    pub x: usize,
+    pub y: usize,
+    pub modifier: TerrainModifier,
+}
+
+impl Level {
+    pub fn new(name: impl Into<String>, tiles: Vec<Vec<u32>>) -> Self {
+        Level {
+            name: name.into(),
+            tiles,
+            terrain: None,
+            branch_weights: Vec::new(),
+            flight_path: Vec::new(),
+            modifiers: Vec::new(),
+            waves: Vec::new(),
+        }
+    }
+
+    // Modificateur de terrain de la case (x, y), s'il y en a un
+    pub fn get_modifier(&self, x: usize, y: usize) -> Option<TerrainModifier> {
+        self.modifiers.iter()
+            .find(|cell| cell.x == x && cell.y == y)
+            .map(|cell| cell.modifier)
+    }
+
+    // Multiplicateur de vitesse des ennemis au sol sur la case (x, y)
+    pub fn get_speed_multiplier(&self, x: usize, y: usize) -> f32 {
+        self.get_modifier(x, y).map_or(1.0, |modifier| modifier.get_speed_multiplier())
+    }
+
+    pub fn width(&self) -> usize {
+        self.tiles.first().map_or(0, |row| row.len())
+    }
+
+    pub fn height(&self) -> usize {
+        self.tiles.len()
+    }
+}
+
+// Taille d'une tuile dans le monde (et dans l'atlas)
+pub const TILE_SIZE: f32 = 32.0;
+
+// Hauteur de la barre d'UI en bas de l'écran (boutons des tours)
+pub const HUD_HEIGHT: f32 = 100.0;
+
+// Conversion grille <-> monde pour le niveau en cours.
+// La carte est centrée sur l'origine du monde, la ligne 0 est en haut.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct GridLayout {
+    pub width: usize,
+    pub height: usize,
+    pub tile_size: f32,
+}
+
+impl GridLayout {
+    pub fn from_level(level: &Level) -> Self {
+        GridLayout { width: level.width(), height: level.height(), tile_size: TILE_SIZE }
+    }
+
+    // Taille de la carte en unités monde
+    pub fn map_size(&self) -> Vec2 {
+        Vec2::new(self.width as f32, self.height as f32) * self.tile_size
+    }
+
+    // Centre de la tuile (x, y) dans le monde
+    pub fn grid_to_world(&self, x: usize, y: usize) -> Vec2 {
+        let size = self.map_size();
+        Vec2::new(
+            -size.x / 2.0 + self.tile_size / 2.0 + x as f32 * self.tile_size,
+            size.y / 2.0 - self.tile_size / 2.0 - y as f32 * self.tile_size,
+        )
+    }
+
+    // Tuile sous une position du monde (None si hors de la carte)
+    pub fn world_to_grid(&self, pos: Vec2) -> Option<(usize, usize)> {
+        let size = self.map_size();
+        let grid_x = ((pos.x + size.x / 2.0) / self.tile_size).floor();
+        let grid_y = ((size.y / 2.0 - pos.y) / self.tile_size).floor();
+
+        if grid_x < 0.0 || grid_y < 0.0 { return None; }
+        let (ix, iy) = (grid_x as usize, grid_y as usize);
+        if ix >= self.width || iy >= self.height { return None; }
+        Some((ix, iy))
+    }
+
+    // Tuile sous le curseur (None hors de la carte ou sur la barre d'UI du bas)
+    pub fn cursor_to_grid(&self, window: &Window, camera: &Camera, camera_transform: &GlobalTransform) -> Option<(usize, usize)> {
+        let cursor = window.cursor_position()?;
+        if cursor.y > window.height() - HUD_HEIGHT { return None; }
+
+        let world_position = camera.viewport_to_world(camera_transform, cursor)?.origin.truncate();
+        self.world_to_grid(world_position)
+    }
+}
+
+// Liste de tous les niveaux trouvés dans "assets/levels" (.level.ron, et cartes Tiled
+// .tmx / .tmj), triés par nom de fichier
+#[derive(Resource, Default)]
+pub struct LevelList {
+    pub levels: Vec<Handle<Level>>,
+}
+
+// Le niveau qui sera lancé par setup_game / setup_simulation
+#[derive(Resource, Default)]
+pub struct SelectedLevel {
+    pub handle: Handle<Level>,
+}
+
+// Handle du dossier, gardé le temps du chargement
+#[derive(Resource)]
+struct LevelFolder(Handle<LoadedFolder>);
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .init_asset_loader::<TiledLoader>()
+            .init_resource::<LevelList>()
+            .init_resource::<SelectedLevel>()
+            .add_systems(Startup, load_levels)
+            .add_systems(Update, (
+                wait_for_levels.run_if(in_state(AppState::Loading)),
+                fit_camera_to_map.run_if(resource_exists_and_changed::<GridLayout>),
+            ));
+    }
+}
+
+// --- Chargement des fichiers de niveau ---
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+#[derive(Debug, Error)]
+pub enum LevelLoaderError {
+    #[error("impossible de lire le fichier de niveau : {0}")]
+    Io(#[from] std::io::Error),
+    #[error("fichier de niveau invalide : {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("la carte doit être rectangulaire et non vide")]
+    NotRectangular,
+    #[error("terrain inconnu {id} en ({x},{y})")]
+    UnknownTerrain { id: u32, x: usize, y: usize },
+}
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Level, LevelLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            parse_level(&bytes)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+// Lecture d'un niveau au format RON (partagé par le chargeur d'assets et l'éditeur)
+pub fn parse_level(bytes: &[u8]) -> Result<Level, LevelLoaderError> {
+    let mut level = ron::de::from_bytes::<Level>(bytes)?;
+
+    if let Some(terrain) = level.terrain.take() {
+        for (y, row) in terrain.iter().enumerate() {
+            for (x, &id) in row.iter().enumerate() {
+                if id > TerrainTypes::END_TILE {
+                    return Err(LevelLoaderError::UnknownTerrain { id, x, y });
+                }
+            }
+        }
+        level.tiles = autotile(&terrain);
+    }
+
+    // Toutes les lignes doivent avoir la même largeur
+    let width = level.width();
+    if width == 0 || level.tiles.iter().any(|row| row.len() != width) {
+        return Err(LevelLoaderError::NotRectangular);
+    }
+    Ok(level)
+}
+
+// Écriture d'un niveau au format RON, une ligne de la grille par ligne de texte
+pub fn serialize_level(level: &Level) -> Result<String, ron::Error> {
+    let config = ron::ser::PrettyConfig::new().depth_limit(2);
+    ron::ser::to_string_pretty(level, config)
+}
+
+fn load_levels(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(LevelFolder(asset_server.load_folder("levels")));
+}
+
+// Attend que tous les niveaux soient chargés avant d'afficher le menu
+fn wait_for_levels(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    folder: Res<LevelFolder>,
+    folders: Res<Assets<LoadedFolder>>,
+    levels: Res<Assets<Level>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    match asset_server.get_recursive_dependency_load_state(&folder.0) {
+        Some(RecursiveDependencyLoadState::Loaded) | Some(RecursiveDependencyLoadState::Failed) => {}
+        _ => return, // Toujours en cours de chargement
+    }
+
+    let mut level_handles: Vec<Handle<Level>> = folders
+        .get(&folder.0)
+        .map(|loaded| {
+            loaded.handles.iter()
+                .filter_map(|handle| handle.clone().try_typed::<Level>().ok())
+                .filter(|handle| levels.contains(handle)) // On ignore les fichiers invalides
+                .collect()
+        })
+        .unwrap_or_default();
+    level_handles.sort_by_key(|handle| handle.path().map(|path| path.to_string()));
+
+    println!("{} niveau(x) chargé(s)", level_handles.len());
+
+    if let Some(first) = level_handles.first() {
+        commands.insert_resource(SelectedLevel { handle: first.clone() });
+    }
+    commands.insert_resource(LevelList { levels: level_handles });
+    commands.remove_resource::<LevelFolder>();
+    next_state.set(AppState::Menu);
+}
+
+// Zoome la caméra pour que toute la carte tienne au-dessus de la barre d'UI
+fn fit_camera_to_map(
+    layout: Res<GridLayout>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut camera_q: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+) {
+    let Ok(window) = windows.get_single() else { return };
+    let map_size = layout.map_size();
+    let available = Vec2::new(window.width(), window.height() - HUD_HEIGHT);
+    let scale = (map_size.x / available.x).max(map_size.y / available.y);
+
+    for (mut transform, mut projection) in camera_q.iter_mut() {
+        projection.scale = scale;
+        // On descend la caméra de la moitié de la barre d'UI pour centrer la carte dans la zone visible
+        transform.translation.x = 0.0;
+        transform.translation.y = -HUD_HEIGHT / 2.0 * scale;
+    }
+}
+
+// --- Lecture des données d'un niveau ---
+
+// Toutes les erreurs qui rendent un niveau injouable
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LevelError {
+    #[error("pas de point de départ")]
+    MissingStart,
+    #[error("pas de point de fin")]
+    MissingEnd,
+    #[error("plusieurs points de fin ({x},{y})")]
+    DuplicateEnd { x: usize, y: usize },
+    #[error("route non reliée au chemin en ({x},{y})")]
+    DisconnectedRoad { x: usize, y: usize },
+    #[error("le chemin s'arrête en ({x},{y}) sans atteindre la fin")]
+    PathNeverReachesEnd { x: usize, y: usize },
+    #[error("tuile inconnue {id} en ({x},{y})")]
+    UnknownTile { id: u32, x: usize, y: usize },
+    #[error("point de vol ({x},{y}) en dehors de la carte")]
+    FlightPointOutside { x: usize, y: usize },
+    #[error("modificateur de terrain hors de la route en ({x},{y})")]
+    ModifierOffRoad { x: usize, y: usize },
+}
+
+// Id de tuile le plus grand connu (21 = END_PATH)
+pub const MAX_TILE_ID: u32 = 21;
+
+pub fn get_level_data(level: &Level) -> &[Vec<u32>] {
+    &level.tiles
+}
+
+// Tous les points de départ, dans l'ordre de lecture de la carte
+pub fn get_start_points(level: &Level) -> Result<Vec<PathPoint>, LevelError> {
+    let starts = find_tiles(level, 20);
+    if starts.is_empty() { return Err(LevelError::MissingStart); }
+    Ok(starts)
+}
+
+pub fn get_end_point(level: &Level) -> Result<PathPoint, LevelError> {
+    match find_tiles(level, 21).as_slice() {
+        [] => Err(LevelError::MissingEnd),
+        [end] => Ok(*end),
+        [_, duplicate, ..] => Err(LevelError::DuplicateEnd { x: duplicate.x, y: duplicate.y }),
+    }
+}
+
+fn find_tiles(level: &Level, wanted_id: u32) -> Vec<PathPoint> {
+    let mut found = Vec::new();
+    for (y, row) in level.tiles.iter().enumerate() {
         for (x, &tile_id) in row.iter().enumerate() {
-            if tile_id == 20 { // 20 est le START_PATH
-                return PathPoint { x, y };
+            if tile_id == wanted_id { found.push(PathPoint { x, y }); }
+        }
+    }
+    found
+}
+
+// --- Graphe du chemin ---
+//
+// Le chemin n'est plus une simple suite de cases : c'est un graphe de tronçons.
+// Un tronçon va d'un "noeud" (départ, arrivée, carrefour) au suivant ; à la fin d'un
+// tronçon l'ennemi choisit parmi `next` (embranchement), et plusieurs tronçons
+// peuvent aboutir au même noeud (chemins qui se rejoignent).
+
+// Un tronçon de route, orienté vers l'arrivée
+#[derive(Debug, Clone)]
+pub struct PathSegment {
+    // Cases du tronçon, noeud de départ et noeud d'arrivée compris
+    pub cells: Vec<PathPoint>,
+    // Tronçons possibles à la fin de celui-ci (vide = on est arrivé)
+    pub next: Vec<usize>,
+    // Poids de ce tronçon quand on le tire au sort à un embranchement
+    pub weight: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PathGraph {
+    pub segments: Vec<PathSegment>,
+    // Tronçons qui partent d'un point de départ
+    pub starts: Vec<usize>,
+}
+
+impl PathGraph {
+    // Toutes les cases parcourues par au moins un tronçon
+    pub fn cells(&self) -> Vec<PathPoint> {
+        let mut cells: Vec<PathPoint> = Vec::new();
+        for segment in self.segments.iter() {
+            for cell in segment.cells.iter() {
+                if !cells.contains(cell) { cells.push(*cell); }
             }
         }
+        cells
     }
-    panic!("Pas de point de départ trouvé !");
 }
 
-pub fn get_end_point() -> PathPoint {
-    for (y, row) in LEVEL_DATA.iter().enumerate() {
+fn is_path_tile(tile_id: u32) -> bool {
+    matches!(get_tile_type(tile_id), TileType::Road | TileType::Start | TileType::End)
+}
+
+// Cases de route voisines, dans l'ordre Haut, Bas, Gauche, Droite
+fn road_neighbors(level: &Level, cell: PathPoint) -> Vec<PathPoint> {
+    let (x, y) = (cell.x as i32, cell.y as i32);
+    [(0, -1), (0, 1), (-1, 0), (1, 0)].iter()
+        .map(|(dx, dy)| (x + dx, y + dy))
+        .filter(|&(nx, ny)| nx >= 0 && ny >= 0 && (nx as usize) < level.width() && (ny as usize) < level.height())
+        .map(|(nx, ny)| PathPoint { x: nx as usize, y: ny as usize })
+        .filter(|p| is_path_tile(level.tiles[p.y][p.x]))
+        .collect()
+}
+
+// Construit le graphe du chemin :
+// 1. on découpe la route en couloirs entre deux noeuds (départs, arrivée, carrefours, culs-de-sac),
+// 2. on calcule pour chaque noeud sa distance à l'arrivée,
+// 3. chaque couloir devient un tronçon orienté du noeud le plus loin vers le plus proche de l'arrivée.
+// Un couloir dont les deux bouts sont à la même distance n'est jamais emprunté.
+pub fn build_path_graph(level: &Level) -> Result<PathGraph, LevelError> {
+    let starts = get_start_points(level)?;
+    let end = get_end_point(level)?;
+    let is_node = |cell: PathPoint| {
+        cell == end || starts.contains(&cell) || road_neighbors(level, cell).len() != 2
+    };
+
+    // 1. Couloirs (non orientés)
+    let mut corridors: Vec<Vec<PathPoint>> = Vec::new();
+    let mut walked: HashSet<(PathPoint, PathPoint)> = HashSet::new();
+    for (y, row) in level.tiles.iter().enumerate() {
         for (x, &tile_id) in row.iter().enumerate() {
-            if tile_id == 21 { // 21 est le END_PATH
-                return PathPoint { x, y };
+            let node = PathPoint { x, y };
+            if !is_path_tile(tile_id) || !is_node(node) { continue; }
+
+            for first_step in road_neighbors(level, node) {
+                if walked.contains(&(node, first_step)) { continue; }
+                let mut cells = vec![node, first_step];
+                let (mut previous, mut current) = (node, first_step);
+                while !is_node(current) {
+                    // Case de couloir : exactement deux voisins, on prend celui d'où on ne vient pas
+                    let Some(next) = road_neighbors(level, current).into_iter().find(|&c| c != previous) else { break };
+                    previous = current;
+                    current = next;
+                    cells.push(current);
+                }
+                walked.insert((node, first_step));
+                walked.insert((current, previous));
+                corridors.push(cells);
+            }
+        }
+    }
+
+    // 2. Distance de chaque noeud à l'arrivée (en cases)
+    let mut distances: HashMap<PathPoint, usize> = HashMap::from([(end, 0)]);
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for cells in corridors.iter() {
+            let (a, b) = (cells[0], cells[cells.len() - 1]);
+            let length = cells.len() - 1;
+            for (from, to) in [(a, b), (b, a)] {
+                let Some(&to_distance) = distances.get(&to) else { continue };
+                let new_distance = to_distance + length;
+                let shorter = match distances.get(&from) {
+                    Some(&distance) => new_distance < distance,
+                    None => true,
+                };
+                if shorter {
+                    distances.insert(from, new_distance);
+                    changed = true;
+                }
             }
         }
     }
-    panic!("Pas de point de fin trouvé !");
-}
\ No newline at end of file
+
+    // Un départ qui n'atteint pas l'arrivée : on signale le cul-de-sac où il s'arrête
+    for &start in starts.iter() {
+        if !distances.contains_key(&start) {
+            let dead_end = find_dead_end(level, start).unwrap_or(start);
+            return Err(LevelError::PathNeverReachesEnd { x: dead_end.x, y: dead_end.y });
+        }
+    }
+
+    // 3. Tronçons orientés vers l'arrivée
+    let mut segments: Vec<PathSegment> = Vec::new();
+    for mut cells in corridors {
+        let distance = |cell: &PathPoint| distances.get(cell).copied().unwrap_or(usize::MAX);
+        let (first, last) = (distance(&cells[0]), distance(&cells[cells.len() - 1]));
+        if first == last { continue; }
+        if first < last { cells.reverse(); }
+
+        let weight = level.branch_weights.iter()
+            .find(|w| w.x == cells[1].x && w.y == cells[1].y)
+            .map_or(1.0, |w| w.weight);
+        segments.push(PathSegment { cells, next: Vec::new(), weight });
+    }
+    for i in 0..segments.len() {
+        let last = segments[i].cells[segments[i].cells.len() - 1];
+        segments[i].next = (0..segments.len()).filter(|&j| segments[j].cells[0] == last).collect();
+    }
+    let start_segments: Vec<usize> = (0..segments.len())
+        .filter(|&i| starts.contains(&segments[i].cells[0]))
+        .collect();
+
+    // Toute route doit pouvoir être empruntée depuis un départ
+    let mut reachable = vec![false; segments.len()];
+    let mut to_visit = start_segments.clone();
+    while let Some(i) = to_visit.pop() {
+        if reachable[i] { continue; }
+        reachable[i] = true;
+        to_visit.extend(segments[i].next.iter().copied());
+    }
+    for (y, row) in level.tiles.iter().enumerate() {
+        for (x, &tile_id) in row.iter().enumerate() {
+            if get_tile_type(tile_id) != TileType::Road { continue; }
+            let cell = PathPoint { x, y };
+            let used = segments.iter().zip(reachable.iter())
+                .any(|(segment, &reached)| reached && segment.cells.contains(&cell));
+            if !used {
+                return Err(LevelError::DisconnectedRoad { x, y });
+            }
+        }
+    }
+
+    Ok(PathGraph { segments, starts: start_segments })
+}
+
+// Premier cul-de-sac (case de route avec un seul voisin) accessible depuis une case
+fn find_dead_end(level: &Level, from: PathPoint) -> Option<PathPoint> {
+    let mut visited = vec![from];
+    let mut to_visit = vec![from];
+    while let Some(cell) = to_visit.pop() {
+        let neighbors = road_neighbors(level, cell);
+        if cell != from && neighbors.len() <= 1 { return Some(cell); }
+        for neighbor in neighbors {
+            if !visited.contains(&neighbor) {
+                visited.push(neighbor);
+                to_visit.push(neighbor);
+            }
+        }
+    }
+    None
+}
+
+// Vérifie qu'un niveau est jouable et retourne le graphe de son chemin si c'est le cas
+pub fn validate_level(level: &Level) -> Result<PathGraph, LevelError> {
+    for (y, row) in level.tiles.iter().enumerate() {
+        for (x, &id) in row.iter().enumerate() {
+            if id > MAX_TILE_ID {
+                return Err(LevelError::UnknownTile { id, x, y });
+            }
+        }
+    }
+
+    if let Some(&(x, y)) = level.flight_path.iter().find(|&&(x, y)| x >= level.width() || y >= level.height()) {
+        return Err(LevelError::FlightPointOutside { x, y });
+    }
+
+    // Les modificateurs ne concernent que les ennemis au sol : ils n'ont de sens que sur la route
+    for cell in level.modifiers.iter() {
+        let tile_type = level.tiles.get(cell.y).and_then(|row| row.get(cell.x)).map(|&id| get_tile_type(id));
+        if !matches!(tile_type, Some(TileType::Road | TileType::Start | TileType::End)) {
+            return Err(LevelError::ModifierOffRoad { x: cell.x, y: cell.y });
+        }
+    }
+
+    build_path_graph(level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiled::{parse_tiled, TiledFormat};
+
+    // 0 : herbe, 2 : route, 20 : départ, 21 : arrivée
+    fn check(tiles: Vec<Vec<u32>>) -> Result<PathGraph, LevelError> {
+        validate_level(&Level::new("test", tiles))
+    }
+
+    #[test]
+    fn missing_start() {
+        assert_eq!(check(vec![vec![0, 2, 21]]).unwrap_err(), LevelError::MissingStart);
+    }
+
+    #[test]
+    fn several_starts_are_allowed() {
+        let graph = check(vec![vec![20, 2, 21, 2, 20]]).unwrap();
+        assert_eq!(graph.starts.len(), 2);
+    }
+
+    #[test]
+    fn missing_end() {
+        assert_eq!(check(vec![vec![20, 2, 2]]).unwrap_err(), LevelError::MissingEnd);
+    }
+
+    #[test]
+    fn duplicate_end() {
+        let tiles = vec![
+            vec![20, 2, 21],
+            vec![0, 0, 21],
+        ];
+        assert_eq!(check(tiles).unwrap_err(), LevelError::DuplicateEnd { x: 2, y: 1 });
+    }
+
+    #[test]
+    fn disconnected_road() {
+        let tiles = vec![
+            vec![20, 2, 21],
+            vec![0, 0, 0],
+            vec![0, 2, 2],
+        ];
+        assert_eq!(check(tiles).unwrap_err(), LevelError::DisconnectedRoad { x: 1, y: 2 });
+    }
+
+    #[test]
+    fn end_unreachable() {
+        assert_eq!(check(vec![vec![20, 2, 0, 21]]).unwrap_err(), LevelError::PathNeverReachesEnd { x: 1, y: 0 });
+    }
+
+    #[test]
+    fn unknown_tile() {
+        assert_eq!(check(vec![vec![20, 2, 21, 99]]).unwrap_err(), LevelError::UnknownTile { id: 99, x: 3, y: 0 });
+    }
+
+    #[test]
+    fn bundled_levels_are_valid() {
+        let levels = [
+            include_bytes!("../assets/levels/niveau_01.level.ron").as_slice(),
+            include_bytes!("../assets/levels/niveau_02.level.ron"),
+            include_bytes!("../assets/levels/niveau_03.level.ron"),
+            include_bytes!("../assets/levels/niveau_04.level.ron"),
+            include_bytes!("../assets/levels/niveau_05.level.ron"),
+        ];
+        for bytes in levels {
+            let level = parse_level(bytes).unwrap();
+            assert!(validate_level(&level).is_ok(), "{}", level.name);
+        }
+        let tmx = parse_tiled(include_bytes!("../assets/levels/niveau_06.tmx"), TiledFormat::Tmx, "niveau_06").unwrap();
+        assert!(validate_level(&tmx).is_ok(), "{}", tmx.name);
+    }
+}
@@ -0,0 +1,361 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+use crate::{
+    AppState,
+    level::{self, Level, LevelList, SelectedLevel},
+    game::{get_tile_type, TileType, Difficulty},
+    menu::{NORMAL_BUTTON, HOVERED_BUTTON, PRESSED_BUTTON},
+    generator::{generate_level, GeneratorParams},
+    maze::GameMode,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Marqueur pour tout ce qui appartient à l'écran de sélection
+#[derive(Component)]
+struct LevelSelectUI;
+
+// Actions des boutons de l'écran de sélection
+#[derive(Component)]
+enum LevelSelectAction {
+    Select(Handle<Level>),
+    Random,
+    ToggleMode,
+    ToggleDifficulty,
+    Back,
+}
+
+#[derive(Component)]
+struct LevelSelectErrorText;
+
+#[derive(Component)]
+struct GameModeText;
+
+#[derive(Component)]
+struct DifficultyText;
+
+// Taille d'affichage d'une miniature (en pixels)
+const THUMBNAIL_SIZE: f32 = 120.0;
+// Dimensions des cartes générées aléatoirement
+const RANDOM_LEVEL_SIZE: (usize, usize) = (20, 20);
+
+// Écran entre le bouton PLAY du menu et AppState::Playing
+pub struct LevelSelectPlugin;
+
+impl Plugin for LevelSelectPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(AppState::LevelSelect), setup_level_select)
+            .add_systems(Update, level_select_interaction.run_if(in_state(AppState::LevelSelect)))
+            .add_systems(OnExit(AppState::LevelSelect), cleanup_level_select);
+    }
+}
+
+fn setup_level_select(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    level_list: Res<LevelList>,
+    levels: Res<Assets<Level>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    println!("Sélection du niveau !");
+
+    let text_style = TextStyle {
+        font_size: 24.0,
+        color: Color::WHITE,
+        ..default()
+    };
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: Color::rgb(0.1, 0.1, 0.1).into(),
+            ..default()
+        },
+        LevelSelectUI,
+    ))
+    .with_children(|parent| {
+        parent.spawn(TextBundle::from_section("CHOIX DU NIVEAU", TextStyle {
+            font_size: 36.0,
+            ..text_style.clone()
+        }));
+
+        // Une carte par niveau : miniature + nom
+        parent.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                justify_content: JustifyContent::Center,
+                margin: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for handle in level_list.levels.iter() {
+                let Some(level) = levels.get(handle) else { continue; };
+                let thumbnail = images.add(render_thumbnail(level));
+                // On garde les proportions de la carte dans un carré de THUMBNAIL_SIZE
+                let longest_side = level.width().max(level.height()) as f32;
+                let tile_px = THUMBNAIL_SIZE / longest_side;
+
+                parent.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            padding: UiRect::all(Val::Px(8.0)),
+                            margin: UiRect::all(Val::Px(8.0)),
+                            ..default()
+                        },
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    },
+                    LevelSelectAction::Select(handle.clone()),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(ImageBundle {
+                        style: Style {
+                            width: Val::Px(level.width() as f32 * tile_px),
+                            height: Val::Px(level.height() as f32 * tile_px),
+                            margin: UiRect::bottom(Val::Px(6.0)),
+                            ..default()
+                        },
+                        image: UiImage::new(thumbnail),
+                        ..default()
+                    });
+                    parent.spawn(TextBundle::from_section(level.name.clone(), TextStyle {
+                        font_size: 18.0,
+                        ..text_style.clone()
+                    }));
+                });
+            }
+        });
+
+        // --- Bouton "ALÉATOIRE" : carte générée (la graine est dans le nom du niveau) ---
+        parent.spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(150.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            LevelSelectAction::Random,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("ALÉATOIRE", text_style.clone()));
+        });
+
+        // --- Bouton du mode de jeu : classique ou labyrinthe ---
+        parent.spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(260.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            LevelSelectAction::ToggleMode,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(format!("MODE : {}", game_mode.get_label()), text_style.clone()),
+                GameModeText,
+            ));
+        });
+
+        // --- Bouton de difficulté : facile, normal ou difficile ---
+        parent.spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(320.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            LevelSelectAction::ToggleDifficulty,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(format!("DIFFICULTÉ : {}", difficulty.get_label()), text_style.clone()),
+                DifficultyText,
+            ));
+        });
+
+        // Message d'erreur si le niveau choisi est invalide
+        parent.spawn((
+            TextBundle::from_section("", TextStyle {
+                font_size: 20.0,
+                color: Color::RED,
+                ..default()
+            }),
+            LevelSelectErrorText,
+        ));
+
+        // --- Bouton "RETOUR" ---
+        parent.spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(150.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            LevelSelectAction::Back,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("RETOUR", text_style.clone()));
+        });
+    });
+}
+
+// Miniature : un pixel par tuile, coloré selon le type de tuile
+fn render_thumbnail(level: &Level) -> Image {
+    let height = level.height() as u32;
+    let width = level.width() as u32;
+
+    let mut data = Vec::with_capacity((width * height * 4) as usize);
+    for row in level.tiles.iter() {
+        for &tile_id in row.iter() {
+            let color: [u8; 4] = match get_tile_type(tile_id) {
+                TileType::Water => [52, 112, 178, 255],
+                TileType::Grass => [86, 160, 62, 255],
+                TileType::Road => [196, 164, 112, 255],
+                TileType::Start => [40, 200, 40, 255],
+                TileType::End => [220, 40, 40, 255],
+            };
+            data.extend_from_slice(&color);
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d { width, height, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    // Pas de lissage : on veut voir les tuiles nettes une fois agrandies
+    image.sampler = ImageSampler::nearest();
+    image
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn level_select_interaction(
+    mut interaction_query: Query<
+        (&Interaction, &LevelSelectAction, &mut BackgroundColor),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut error_query: Query<&mut Text, (With<LevelSelectErrorText>, Without<GameModeText>, Without<DifficultyText>)>,
+    mut mode_query: Query<&mut Text, (With<GameModeText>, Without<LevelSelectErrorText>, Without<DifficultyText>)>,
+    mut difficulty_query: Query<&mut Text, (With<DifficultyText>, Without<LevelSelectErrorText>, Without<GameModeText>)>,
+    mut game_mode: ResMut<GameMode>,
+    mut difficulty: ResMut<Difficulty>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut selected_level: ResMut<SelectedLevel>,
+    mut levels: ResMut<Assets<Level>>,
+) {
+    for (interaction, action, mut background_color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_BUTTON.into();
+                match action {
+                    LevelSelectAction::Select(handle) => {
+                        let Some(level) = levels.get(handle) else { continue; };
+
+                        // Même règle que le menu : un niveau cassé ne se lance pas
+                        match level::validate_level(level) {
+                            Ok(_) => {
+                                println!("Niveau choisi : {}", level.name);
+                                selected_level.handle = handle.clone();
+                                next_state.set(AppState::Playing);
+                            }
+                            Err(error) => {
+                                let message = format!("Niveau \"{}\" invalide : {error}", level.name);
+                                println!("{message}");
+                                for mut text in error_query.iter_mut() {
+                                    text.sections[0].value = message.clone();
+                                }
+                            }
+                        }
+                    }
+                    LevelSelectAction::Random => {
+                        let seed = SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .map_or(0, |duration| duration.as_nanos() as u64);
+                        let (width, height) = RANDOM_LEVEL_SIZE;
+                        let level = generate_level(seed, width, height, &GeneratorParams::default());
+
+                        println!("Niveau généré : {}", level.name);
+                        selected_level.handle = levels.add(level);
+                        next_state.set(AppState::Playing);
+                    }
+                    LevelSelectAction::ToggleMode => {
+                        *game_mode = game_mode.toggled();
+                        println!("Mode de jeu : {}", game_mode.get_label());
+                        for mut text in mode_query.iter_mut() {
+                            text.sections[0].value = format!("MODE : {}", game_mode.get_label());
+                        }
+                    }
+                    LevelSelectAction::ToggleDifficulty => {
+                        *difficulty = difficulty.next();
+                        println!("Difficulté : {}", difficulty.get_label());
+                        for mut text in difficulty_query.iter_mut() {
+                            text.sections[0].value = format!("DIFFICULTÉ : {}", difficulty.get_label());
+                        }
+                    }
+                    LevelSelectAction::Back => {
+                        next_state.set(AppState::Menu);
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+fn cleanup_level_select(mut commands: Commands, query: Query<Entity, With<LevelSelectUI>>) {
+    println!("Nettoyage de la sélection de niveau...");
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -3,11 +3,14 @@ use bevy::{prelude::*, asset::AssetServer, diagnostic::{FrameTimeDiagnosticsPlug
 // enum de tous les états possibles de l'application (basé sur GameState.java)
 #[derive(States, Debug, Clone, Eq, PartialEq, Hash, Default)]
 pub enum AppState {
-    #[default] // L'état par défaut au lancement
+    #[default] // L'état par défaut au lancement (chargement des niveaux)
+    Loading,
     Menu,
+    LevelSelect,
     Playing,
     Settings,
     Simulation,
+    Editor,
 }
 
 
@@ -16,16 +19,38 @@ pub mod game;
 pub mod settings;
 pub mod constants;
 pub mod level;
+pub mod map;
+pub mod pathfinding;
+pub mod maze;
+pub mod autotile;
+pub mod tiled;
+pub mod generator;
+pub mod level_select;
 pub mod enemy;
+pub mod wave;
+pub mod boss;
+pub mod status;
+pub mod animation;
+pub mod stealth;
 pub mod tower;
 pub mod projectile;
 pub mod simulation;
+pub mod editor;
 
 use menu::MenuPlugin;
 use game::GamePlugin;
 use settings::SettingsPlugin;
 use enemy::EnemyPlugin;
+use wave::WavePlugin;
+use boss::BossPlugin;
+use status::StatusPlugin;
+use animation::AnimationPlugin;
+use stealth::StealthPlugin;
 use simulation::SimulationPlugin;
+use level::LevelPlugin;
+use level_select::LevelSelectPlugin;
+use editor::EditorPlugin;
+use maze::MazePlugin;
 
 use crate::projectile::ProjectilePlugin;
 
@@ -57,12 +82,17 @@ fn main() {
 
         // Ajout des scenes (vu comme des "plugins" Bevy)
         .add_plugins((
+            LevelPlugin,
             MenuPlugin,
+            LevelSelectPlugin,
             GamePlugin,
+            MazePlugin,
             SettingsPlugin,
-            EnemyPlugin,
+            // Ennemis et tout ce qui s'y rattache
+            (EnemyPlugin, WavePlugin, BossPlugin, StatusPlugin, AnimationPlugin, StealthPlugin),
             ProjectilePlugin,
             SimulationPlugin,
+            EditorPlugin,
             FrameTimeDiagnosticsPlugin::default(),
             // LogDiagnosticsPlugin::default(),
         ))
@@ -0,0 +1,456 @@
+use bevy::prelude::*;
+use crate::{
+    AppState,
+    GameAssets,
+    level::{self, Level, SelectedLevel, GridLayout, TerrainModifier},
+    game::{get_atlas_index, get_tile_type, TileType},
+    generator::SeededRng,
+    pathfinding::FlowField,
+};
+
+// Carte partagée par le jeu (Playing) et la simulation : affichage des tuiles,
+// graphe du chemin et conversion grille <-> monde (GridLayout).
+
+// Composant qui sera ajouté à chaque entité "Tuile"
+#[derive(Component)]
+pub struct GameTile {
+    pub tile_type: TileType,
+}
+
+// Un tronçon du chemin en coordonnées monde (voir level::PathGraph)
+#[derive(Debug, Clone)]
+pub struct PathSegment {
+    pub points: Vec<Vec2>, // Le premier point est le carrefour d'où part le tronçon
+    pub next: Vec<usize>,  // Tronçons suivants possibles (vide = arrivée)
+    pub weight: f32,
+    pub curve: PathCurve,  // Courbe lisse passant par `points`, suivie par les ennemis au sol
+}
+
+impl PathSegment {
+    pub fn new(points: Vec<Vec2>, next: Vec<usize>, weight: f32) -> Self {
+        let curve = PathCurve::new(&points);
+        PathSegment { points, next, weight, curve }
+    }
+}
+
+// Nombre d'échantillons de la courbe entre deux points du tronçon
+const CURVE_STEPS: usize = 8;
+
+// Courbe de Catmull-Rom du premier au dernier point du tronçon, échantillonnée et paramétrée
+// par la longueur d'arc : `sample(distance)` donne la position à `distance` pixels du début
+// du tronçon, donc une vitesse constante même dans les virages.
+#[derive(Debug, Clone, Default)]
+pub struct PathCurve {
+    samples: Vec<Vec2>,
+    lengths: Vec<f32>, // Longueur cumulée depuis le début jusqu'à chaque échantillon
+}
+
+impl PathCurve {
+    pub fn new(tile_centers: &[Vec2]) -> Self {
+        // On passe par les milieux entre deux centres de tuiles plutôt que par les centres :
+        // les lignes droites restent droites et les virages sont arrondis au lieu de faire un angle
+        let mut points: Vec<Vec2> = tile_centers.first().copied().into_iter().collect();
+        points.extend(tile_centers.windows(2).map(|pair| pair[0].lerp(pair[1], 0.5)));
+        points.extend(tile_centers.last().filter(|_| tile_centers.len() > 1));
+
+        let mut samples: Vec<Vec2> = points.first().copied().into_iter().collect();
+        let last = points.len().saturating_sub(1);
+        for i in 0..last {
+            // Aux extrémités, on répète le premier/dernier point
+            let (p0, p1, p2, p3) = (points[i.saturating_sub(1)], points[i], points[i + 1], points[(i + 2).min(last)]);
+            for step in 1..=CURVE_STEPS {
+                samples.push(catmull_rom(p0, p1, p2, p3, step as f32 / CURVE_STEPS as f32));
+            }
+        }
+
+        let mut lengths = Vec::with_capacity(samples.len());
+        let mut total = 0.0;
+        for (i, sample) in samples.iter().enumerate() {
+            if i > 0 { total += sample.distance(samples[i - 1]); }
+            lengths.push(total);
+        }
+
+        PathCurve { samples, lengths }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or(0.0)
+    }
+
+    // Position et direction (unitaire, ou nulle si la courbe est un point) à `distance` du début
+    pub fn sample(&self, distance: f32) -> (Vec2, Vec2) {
+        if self.samples.len() < 2 {
+            return (self.samples.first().copied().unwrap_or(Vec2::ZERO), Vec2::ZERO);
+        }
+        // Premier échantillon au-delà de `distance` (recherche dichotomique)
+        let i = self.lengths.partition_point(|&length| length < distance).clamp(1, self.samples.len() - 1);
+        let (a, b) = (self.samples[i - 1], self.samples[i]);
+        let span = self.lengths[i] - self.lengths[i - 1];
+        let t = if span > 0.0 { ((distance - self.lengths[i - 1]) / span).clamp(0.0, 1.0) } else { 0.0 };
+        (a.lerp(b, t), (b - a).normalize_or_zero())
+    }
+
+    // Distance depuis le début du point de la courbe le plus proche de `position`,
+    // et carré de l'écart entre ce point et `position`
+    pub fn get_nearest(&self, position: Vec2) -> (f32, f32) {
+        let Some(&first) = self.samples.first() else { return (0.0, f32::MAX); };
+        let mut nearest = (0.0, first.distance_squared(position));
+        for i in 1..self.samples.len() {
+            let (a, b) = (self.samples[i - 1], self.samples[i]);
+            let span = b - a;
+            let t = if span.length_squared() > 0.0 { ((position - a).dot(span) / span.length_squared()).clamp(0.0, 1.0) } else { 0.0 };
+            let gap = a.lerp(b, t).distance_squared(position);
+            if gap < nearest.1 {
+                nearest = (self.lengths[i - 1] + t * (self.lengths[i] - self.lengths[i - 1]), gap);
+            }
+        }
+        nearest
+    }
+}
+
+fn catmull_rom(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2, t: f32) -> Vec2 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+// Ressource pour stocker le graphe du chemin (départs, embranchements, arrivée)
+#[derive(Resource, Default)]
+pub struct Path {
+    pub segments: Vec<PathSegment>,
+    pub starts: Vec<usize>, // Tronçons qui partent d'un point de départ, dans l'ordre des départs du niveau
+    // Points de passage des ennemis volants, le dernier est l'arrivée
+    pub flight_points: Vec<Vec2>,
+}
+
+impl Path {
+    pub fn from_graph(graph: &level::PathGraph, layout: &GridLayout) -> Self {
+        Path {
+            segments: graph.segments.iter()
+                .map(|segment| PathSegment::new(
+                    segment.cells.iter().map(|cell| layout.grid_to_world(cell.x, cell.y)).collect(),
+                    segment.next.clone(),
+                    segment.weight,
+                ))
+                .collect(),
+            starts: graph.starts.clone(),
+            flight_points: Vec::new(),
+        }
+    }
+
+    // Trajet des ennemis volants : les points de passage du niveau puis l'arrivée
+    pub fn with_flight_path(mut self, waypoints: &[(usize, usize)], layout: &GridLayout) -> Self {
+        self.flight_points = waypoints.iter()
+            .map(|&(x, y)| layout.grid_to_world(x, y))
+            .chain(self.get_end_point())
+            .collect();
+        self
+    }
+
+    // Dernier point du tronçon qui arrive à la fin du chemin
+    pub fn get_end_point(&self) -> Option<Vec2> {
+        self.segments.iter()
+            .find(|segment| segment.next.is_empty() && !segment.points.is_empty())
+            .and_then(|segment| segment.points.last().copied())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+
+    // Tronçon et distance sur ce tronçon du point de la route le plus proche de `position`
+    // (None si le niveau n'a pas de route)
+    pub fn get_nearest(&self, position: Vec2) -> Option<(usize, f32)> {
+        self.segments.iter().enumerate()
+            .map(|(segment, path_segment)| (segment, path_segment.curve.get_nearest(position)))
+            .min_by(|(_, (_, a)), (_, (_, b))| a.total_cmp(b))
+            .map(|(segment, (distance, _))| (segment, distance))
+    }
+
+    // Point `index` du tronçon `segment` (None si on a dépassé la fin du tronçon)
+    pub fn get_point(&self, segment: usize, index: usize) -> Option<Vec2> {
+        self.segments.get(segment)?.points.get(index).copied()
+    }
+
+    // Un tronçon vide est un départ sans route (labyrinthe bloqué) : il garde sa place dans `starts`
+    // pour que les index des départs ne changent pas
+    pub fn has_route(&self, segment: usize) -> bool {
+        self.segments.get(segment).is_some_and(|segment| !segment.points.is_empty())
+    }
+
+    // Tronçon de départ d'un nouvel ennemi : `spawn` impose le départ (données de vague),
+    // sinon tirage pondéré avec `roll` dans [0, 1). Les départs sans route sont ignorés :
+    // un départ imposé sans route est remplacé par un tirage parmi les autres
+    pub fn choose_start(&self, spawn: Option<usize>, roll: f32) -> Option<usize> {
+        if let Some(index) = spawn.filter(|_| !self.starts.is_empty()) {
+            let segment = self.starts[index % self.starts.len()];
+            if self.has_route(segment) { return Some(segment); }
+        }
+        let routable: Vec<usize> = self.starts.iter().copied().filter(|&segment| self.has_route(segment)).collect();
+        self.pick_weighted(&routable, roll)
+    }
+
+    // Tronçon suivant à la fin de `segment` (None = arrivée atteinte) :
+    // `branch` impose la branche à chaque embranchement, sinon tirage pondéré
+    pub fn choose_next(&self, segment: usize, branch: Option<usize>, roll: f32) -> Option<usize> {
+        let next = &self.segments.get(segment)?.next;
+        match branch {
+            Some(index) if !next.is_empty() => Some(next[index % next.len()]),
+            _ => self.pick_weighted(next, roll),
+        }
+    }
+
+    fn pick_weighted(&self, options: &[usize], roll: f32) -> Option<usize> {
+        let total: f32 = options.iter().map(|&i| self.segments[i].weight.max(0.0)).sum();
+        if total <= 0.0 { return options.first().copied(); }
+
+        let mut target = roll * total;
+        for &i in options {
+            let weight = self.segments[i].weight.max(0.0);
+            if target < weight { return Some(i); }
+            target -= weight;
+        }
+        options.last().copied()
+    }
+}
+
+// Tirages au sort des ennemis (départ et embranchements), avec une graine fixe
+// pour que deux parties identiques se déroulent de la même façon
+#[derive(Resource)]
+pub struct PathRng(pub SeededRng);
+
+impl Default for PathRng {
+    fn default() -> Self {
+        PathRng(SeededRng::new(0))
+    }
+}
+
+// Le niveau en cours de partie (copie de l'asset), pour les systèmes qui lisent les tuiles
+#[derive(Resource)]
+pub struct CurrentMap {
+    pub level: Level,
+}
+
+impl CurrentMap {
+    pub fn get_tile(&self, x: usize, y: usize) -> Option<u32> {
+        self.level.tiles.get(y)?.get(x).copied()
+    }
+}
+
+// Les systèmes d'entrée dans un état qui ont besoin de la carte se placent après ce set
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MapSpawnSet;
+
+// Plugin de la carte pour un état : à l'entrée dans `state`, on lit le niveau sélectionné,
+// on fait apparaître ses tuiles avec `marker` (le nettoyage de l'état les retrouve ainsi)
+// et on insère GridLayout, Path, FlowField et CurrentMap, retirés à la sortie de l'état.
+pub struct MapPlugin<M: Component + Clone> {
+    pub state: AppState,
+    pub marker: M,
+}
+
+impl<M: Component + Clone> Plugin for MapPlugin<M> {
+    fn build(&self, app: &mut App) {
+        let marker = self.marker.clone();
+        app
+            .add_systems(OnEnter(self.state.clone()), (
+                move |mut commands: Commands,
+                      assets: Res<GameAssets>,
+                      levels: Res<Assets<Level>>,
+                      selected_level: Res<SelectedLevel>,
+                      mut next_state: ResMut<NextState<AppState>>| {
+                    spawn_map(&mut commands, &assets, &levels, &selected_level, &mut next_state, marker.clone());
+                }
+            ).in_set(MapSpawnSet))
+            .add_systems(OnExit(self.state.clone()), cleanup_map);
+    }
+}
+
+fn spawn_map(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    levels: &Assets<Level>,
+    selected_level: &SelectedLevel,
+    next_state: &mut NextState<AppState>,
+    marker: impl Bundle + Clone,
+) {
+    let Some(level) = levels.get(&selected_level.handle) else {
+        println!("Aucun niveau sélectionné !");
+        commands.insert_resource(Path::default());
+        next_state.set(AppState::Menu);
+        return;
+    };
+    println!("Niveau : {}", level.name);
+
+    // Un niveau invalide ne doit jamais faire planter le jeu : on retourne au menu
+    let path_graph = match level::validate_level(level) {
+        Ok(path_graph) => path_graph,
+        Err(error) => {
+            println!("Niveau invalide : {error}");
+            // Chemin vide : les systèmes d'ennemis ne font rien en attendant le retour au menu
+            commands.insert_resource(Path::default());
+            next_state.set(AppState::Menu);
+            return;
+        }
+    };
+
+    // Configuration de la grille (calculée depuis les dimensions du niveau)
+    let layout = GridLayout::from_level(level);
+
+    // --- Génération Visuelle de la Carte ---
+    for (y, row) in level.tiles.iter().enumerate() {
+        for (x, &tile_id) in row.iter().enumerate() {
+            let pos = layout.grid_to_world(x, y);
+            spawn_tile(commands, assets, pos, tile_id, (marker.clone(), Name::new(format!("Tile ({x},{y})"))));
+        }
+    }
+    for cell in level.modifiers.iter() {
+        spawn_modifier(commands, layout.grid_to_world(cell.x, cell.y), cell.modifier, marker.clone());
+    }
+
+    // --- Calcul du Chemin (Pathfinding) ---
+    // On construit le graphe des tronçons que les ennemis devront suivre
+    let path = Path::from_graph(&path_graph, &layout).with_flight_path(&level.flight_path, &layout);
+    println!(
+        "Chemin calculé avec succès : {} tronçon(s), {} départ(s)",
+        path.segments.len(), path.starts.len()
+    );
+
+    // Champ de flux vers l'arrivée, sur la route : partagé par tous les ennemis qui l'utilisent
+    let goals: Vec<(usize, usize)> = level::get_end_point(level).iter().map(|end| (end.x, end.y)).collect();
+    let flow_field = FlowField::new(level.width(), level.height(), &goals, |x, y| {
+        matches!(get_tile_type(level.tiles[y][x]), TileType::Road | TileType::Start | TileType::End)
+    });
+
+    commands.insert_resource(layout);
+    commands.insert_resource(path);
+    commands.insert_resource(flow_field);
+    commands.insert_resource(PathRng::default());
+    commands.insert_resource(CurrentMap { level: level.clone() });
+}
+
+fn cleanup_map(mut commands: Commands) {
+    commands.remove_resource::<Path>();
+    commands.remove_resource::<GridLayout>();
+    commands.remove_resource::<CurrentMap>();
+    commands.remove_resource::<FlowField>();
+}
+
+// Sprites d'une tuile : (index, rotation) de la tuile de base + surcouche éventuelle
+// (tuiles composites Eau + Sable/Terre, Départ et Arrivée)
+pub fn get_tile_sprites(tile_id: u32) -> ((usize, Quat), Option<(usize, Quat)>) {
+    // Rotations dans l'ordre des ids : 0°, 90° (horaire), 180°, 270°
+    let rotations = [
+        Quat::IDENTITY,
+        Quat::from_rotation_z(-90.0f32.to_radians()),
+        Quat::from_rotation_z(180.0f32.to_radians()),
+        Quat::from_rotation_z(90.0f32.to_radians()),
+    ];
+    let water = (get_atlas_index(0, 0), Quat::IDENTITY);
+
+    match tile_id {
+        // --- Tuiles Simples ---
+        0 => ((get_atlas_index(9, 0), Quat::IDENTITY), None),
+        1 => (water, None),
+        2 => ((get_atlas_index(8, 0), rotations[0]), None),
+        3 => ((get_atlas_index(8, 0), rotations[1]), None),
+        4..=7 => ((get_atlas_index(7, 0), rotations[(tile_id - 4) as usize]), None),
+
+        // --- Tuiles Composites (Eau + Sable/Terre) ---
+        8..=11 => (water, Some((get_atlas_index(5, 0), rotations[(tile_id - 8) as usize]))),
+        12..=15 => (water, Some((get_atlas_index(6, 0), rotations[(tile_id - 12) as usize]))),
+        16..=19 => (water, Some((get_atlas_index(4, 0), rotations[(tile_id - 16) as usize]))),
+
+        // --- Départ et Arrivée (route + lettre) ---
+        20 => ((get_atlas_index(8, 0), Quat::IDENTITY), Some((get_atlas_index(7, 2), Quat::IDENTITY))),
+        21 => ((get_atlas_index(8, 0), Quat::IDENTITY), Some((get_atlas_index(8, 2), Quat::IDENTITY))),
+
+        _ => (water, None),
+    }
+}
+
+// Fait apparaître les sprites d'une tuile. `marker` est ajouté à chaque entité
+// créée pour permettre au plugin appelant de les retrouver (nettoyage, édition...)
+pub fn spawn_tile(commands: &mut Commands, assets: &GameAssets, pos: Vec2, tile_id: u32, marker: impl Bundle + Clone) {
+    let tile_type = get_tile_type(tile_id);
+    let ((base_index, base_rotation), overlay) = get_tile_sprites(tile_id);
+
+    commands.spawn((
+        SpriteSheetBundle {
+            texture: assets.sprite_atlas.clone(),
+            atlas: TextureAtlas { layout: assets.sprite_atlas_layout.clone(), index: base_index },
+            transform: Transform { translation: pos.extend(0.0), rotation: base_rotation, ..default() },
+            ..default()
+        },
+        GameTile { tile_type },
+        marker.clone(),
+    ));
+
+    if let Some((overlay_index, overlay_rotation)) = overlay {
+        commands.spawn((
+            SpriteSheetBundle {
+                texture: assets.sprite_atlas.clone(),
+                atlas: TextureAtlas { layout: assets.sprite_atlas_layout.clone(), index: overlay_index },
+                transform: Transform { translation: pos.extend(0.1), rotation: overlay_rotation, ..default() },
+                ..default()
+            },
+            GameTile { tile_type },
+            marker,
+        ));
+    }
+}
+
+// Teinte d'une case de route modifiée (boue, pont, accélérateur), au-dessus des tuiles
+pub fn spawn_modifier(commands: &mut Commands, pos: Vec2, modifier: TerrainModifier, marker: impl Bundle) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: modifier.get_color(),
+                custom_size: Some(Vec2::splat(level::TILE_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(pos.extend(0.2)),
+            ..default()
+        },
+        marker,
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Trois départs dont le deuxième n'a pas de route
+    fn get_test_path() -> Path {
+        Path {
+            segments: vec![
+                PathSegment::new(vec![Vec2::new(0.0, 0.0), Vec2::new(32.0, 0.0)], Vec::new(), 1.0),
+                PathSegment::new(Vec::new(), Vec::new(), 1.0),
+                PathSegment::new(vec![Vec2::new(0.0, 64.0), Vec2::new(32.0, 64.0)], Vec::new(), 1.0),
+            ],
+            starts: vec![0, 1, 2],
+            flight_points: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn start_indices_stay_stable() {
+        let path = get_test_path();
+        assert_eq!(path.choose_start(Some(0), 0.0), Some(0));
+        assert_eq!(path.choose_start(Some(2), 0.0), Some(2));
+    }
+
+    #[test]
+    fn unroutable_starts_are_skipped() {
+        let path = get_test_path();
+        for i in 0..10 {
+            let roll = i as f32 / 10.0;
+            assert_ne!(path.choose_start(None, roll), Some(1));
+            assert_ne!(path.choose_start(Some(1), roll), Some(1));
+        }
+        assert_eq!(path.get_end_point(), Some(Vec2::new(32.0, 0.0)));
+    }
+}
@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+use crate::{
+    AppState,
+    level::{self, GridLayout},
+    game::{get_tile_type, TileType},
+    map::{CurrentMap, MapSpawnSet, Path, PathSegment},
+    enemy::PathFollower,
+    pathfinding,
+};
+
+// Mode de jeu choisi sur l'écran de sélection du niveau
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GameMode {
+    // Les ennemis suivent la route, les tours se posent sur l'herbe
+    #[default]
+    Classic,
+    // Terrain ouvert : les tours se posent aussi sur la route et les ennemis les contournent (A*)
+    Maze,
+}
+
+impl GameMode {
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "CLASSIQUE",
+            GameMode::Maze => "LABYRINTHE",
+        }
+    }
+
+    pub fn toggled(&self) -> GameMode {
+        match self {
+            GameMode::Classic => GameMode::Maze,
+            GameMode::Maze => GameMode::Classic,
+        }
+    }
+}
+
+// Grille du mode labyrinthe : cases praticables (herbe, route, départ, arrivée)
+// et cases occupées par une tour. Présente seulement en mode GameMode::Maze.
+#[derive(Resource)]
+pub struct MazeGrid {
+    pub width: usize,
+    pub height: usize,
+    walkable: Vec<Vec<bool>>,
+    towers: Vec<Vec<bool>>,
+    pub starts: Vec<(usize, usize)>,
+    pub end: (usize, usize),
+}
+
+impl MazeGrid {
+    // None si le niveau n'a pas de départ ou d'arrivée (déjà refusé par validate_level)
+    pub fn from_level(level: &level::Level) -> Option<Self> {
+        let starts = level::get_start_points(level).ok()?;
+        let end = level::get_end_point(level).ok()?;
+        let walkable = level.tiles.iter()
+            .map(|row| row.iter().map(|&tile_id| get_tile_type(tile_id) != TileType::Water).collect())
+            .collect();
+
+        Some(MazeGrid {
+            width: level.width(),
+            height: level.height(),
+            walkable,
+            towers: vec![vec![false; level.width()]; level.height()],
+            starts: starts.iter().map(|point| (point.x, point.y)).collect(),
+            end: (end.x, end.y),
+        })
+    }
+
+    // Un ennemi peut-il passer par cette case ?
+    pub fn is_open(&self, x: usize, y: usize) -> bool {
+        self.walkable[y][x] && !self.towers[y][x]
+    }
+
+    // Plus court chemin de `from` à l'arrivée en contournant les tours
+    pub fn find_route(&self, from: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        pathfinding::astar(self.width, self.height, from, self.end, |x, y| self.is_open(x, y))
+    }
+
+    // Même chose en ignorant les tours (seul l'eau arrête l'ennemi)
+    pub fn find_route_through_towers(&self, from: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        pathfinding::astar(self.width, self.height, from, self.end, |x, y| self.walkable[y][x])
+    }
+
+    // Une tour peut aller sur une case libre d'herbe ou de route (pas sur le départ ni l'arrivée),
+    // à condition que chaque départ garde une route jusqu'à l'arrivée
+    pub fn can_build(&self, x: usize, y: usize) -> bool {
+        if x >= self.width || y >= self.height || !self.is_open(x, y) { return false; }
+        if self.starts.contains(&(x, y)) || self.end == (x, y) { return false; }
+
+        let is_open = |cx: usize, cy: usize| (cx, cy) != (x, y) && self.is_open(cx, cy);
+        self.starts.iter()
+            .all(|&start| pathfinding::astar(self.width, self.height, start, self.end, is_open).is_some())
+    }
+
+    pub fn add_tower(&mut self, x: usize, y: usize) {
+        self.towers[y][x] = true;
+    }
+}
+
+// Mode labyrinthe : à chaque tour posée, les ennemis recalculent leur route avec A*
+pub struct MazePlugin;
+
+impl Plugin for MazePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<GameMode>()
+            .add_systems(OnEnter(AppState::Playing), setup_maze
+                .after(MapSpawnSet)
+                .run_if(resource_equals(GameMode::Maze)))
+            // Aussi à la première frame : la grille vient d'être insérée
+            .add_systems(Update, repath_enemies
+                .run_if(in_state(AppState::Playing))
+                .run_if(resource_exists_and_changed::<MazeGrid>))
+            .add_systems(OnExit(AppState::Playing), cleanup_maze);
+    }
+}
+
+fn setup_maze(mut commands: Commands, map: Option<Res<CurrentMap>>) {
+    // Pas de carte : niveau invalide, MapPlugin nous renvoie au menu
+    let Some(map) = map else { return; };
+    let Some(maze) = MazeGrid::from_level(&map.level) else { return; };
+    println!("Mode labyrinthe : {} départ(s), terrain ouvert", maze.starts.len());
+    commands.insert_resource(maze);
+}
+
+// Reconstruit le chemin : un tronçon A* par départ pour les nouveaux ennemis (vide si le départ
+// n'a plus de route, pour garder les index des départs), puis un tronçon par ennemi déjà en route,
+// depuis la case où il se trouve
+fn repath_enemies(
+    maze: Res<MazeGrid>,
+    layout: Res<GridLayout>,
+    mut path: ResMut<Path>,
+    mut followers: Query<(&Transform, &mut PathFollower)>,
+) {
+    let to_world = |route: &[(usize, usize)]| route.iter().map(|&(x, y)| layout.grid_to_world(x, y)).collect::<Vec<_>>();
+
+    let mut segments = Vec::new();
+    let mut starts = Vec::new();
+    for &start in maze.starts.iter() {
+        let route = maze.find_route(start).unwrap_or_default();
+        starts.push(segments.len());
+        segments.push(PathSegment::new(to_world(&route), Vec::new(), 1.0));
+    }
+
+    for (transform, mut follower) in followers.iter_mut() {
+        let Some(cell) = layout.world_to_grid(transform.translation.truncate()) else { continue; };
+        // Ennemi enfermé par les tours : il passe au travers plutôt que de rester bloqué
+        let Some(route) = maze.find_route(cell).or_else(|| maze.find_route_through_towers(cell)) else { continue; };
+        // Le nouveau tronçon part de la position exacte de l'ennemi (au lieu du centre de sa case)
+        let rest = if route.len() > 1 { &route[1..] } else { &route[..] };
+        let points = std::iter::once(transform.translation.truncate()).chain(to_world(rest)).collect();
+        follower.segment = segments.len();
+        follower.distance = 0.0;
+        segments.push(PathSegment::new(points, Vec::new(), 1.0));
+    }
+
+    path.segments = segments;
+    path.starts = starts;
+}
+
+fn cleanup_maze(mut commands: Commands) {
+    commands.remove_resource::<MazeGrid>();
+}
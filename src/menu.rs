@@ -3,24 +3,30 @@ use bevy::{
     app::AppExit};
 use crate::AppState; 
 use crate::GameAssets;
+use crate::level::{self, Level, LevelList, SelectedLevel};
 
 // Composant vide pour marquer les entitées créées par le menu pour les trouver et les supprimer facilement si besoin
 #[derive(Component)]
 struct MenuUI;
 
+// Texte d'erreur affiché sous les boutons (ex: niveau invalide)
+#[derive(Component)]
+struct MenuErrorText;
+
 // Composant pour les boutons, avec les actions associées
 #[derive(Component)]
 enum MenuButtonAction {
     Play,
     Simulation,
+    Editor,
     Settings,
     Quit,
 }
 
 // Couleurs des boutons
-const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
-const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
-const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+pub const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+pub const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
+pub const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
 
 // équivalent de la classe "Menu.java"
 pub struct MenuPlugin;
@@ -120,6 +126,18 @@ fn setup_menu(mut commands: Commands, assets: Res<GameAssets>) {
         .with_children(|parent| {
             parent.spawn(TextBundle::from_section("SIMULATION", text_style.clone()));
         });
+        // --- Bouton "EDITOR" ---
+        parent.spawn((
+            ButtonBundle {
+                style: button_style.clone(),
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            MenuButtonAction::Editor,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("EDITOR", text_style.clone()));
+        });
         // --- Bouton "SETTINGS" ---
         parent.spawn((
             ButtonBundle {
@@ -145,9 +163,29 @@ fn setup_menu(mut commands: Commands, assets: Res<GameAssets>) {
         .with_children(|parent| {
             parent.spawn(TextBundle::from_section("QUIT", text_style.clone()));
         });
+
+        // --- Message d'erreur (vide par défaut) ---
+        parent.spawn((
+            TextBundle::from_section("", TextStyle {
+                font_size: 20.0,
+                color: Color::RED,
+                ..default()
+            }),
+            MenuErrorText,
+        ));
     });
 }
 
+// Retourne un message si le niveau sélectionné ne peut pas être lancé
+fn selected_level_error(levels: &Assets<Level>, selected_level: &SelectedLevel) -> Option<String> {
+    match levels.get(&selected_level.handle) {
+        None => Some("Aucun niveau disponible".to_string()),
+        Some(level) => level::validate_level(level)
+            .err()
+            .map(|error| format!("Niveau \"{}\" invalide : {error}", level.name)),
+    }
+}
+
 // nettoyage du menu quand on en sort
 fn cleanup_menu(mut commands: Commands, query: Query<Entity, With<MenuUI>>) {
     println!("Nettoyage du Menu...");
@@ -158,6 +196,7 @@ fn cleanup_menu(mut commands: Commands, query: Query<Entity, With<MenuUI>>) {
 }
 
 // systeme d'interaction avec les boutons
+#[allow(clippy::too_many_arguments)]
 fn button_interaction_system(
     mut interaction_query: Query<
         (Entity, &Interaction, &MenuButtonAction), // récupération de l'action
@@ -166,6 +205,10 @@ fn button_interaction_system(
     mut next_state: ResMut<NextState<AppState>>, // Pour changer l'état
     mut app_exit_writer: EventWriter<AppExit>, // Pour quitter le jeu
     mut button_query: Query<&mut BackgroundColor, With<Button>>, // Pour changer la couleur
+    mut error_query: Query<&mut Text, With<MenuErrorText>>,
+    levels: Res<Assets<Level>>,
+    selected_level: Res<SelectedLevel>,
+    level_list: Res<LevelList>,
 ) {
     for (entity, interaction, action) in interaction_query.iter_mut() {
         
@@ -178,11 +221,29 @@ fn button_interaction_system(
                 match action {
                     MenuButtonAction::Play => {
                         println!("Bouton Play cliqué !");
-                        next_state.set(AppState::Playing); // Change l'état
+                        if level_list.levels.is_empty() {
+                            for mut text in error_query.iter_mut() {
+                                text.sections[0].value = "Aucun niveau disponible".to_string();
+                            }
+                        } else {
+                            next_state.set(AppState::LevelSelect); // Choix du niveau avant de jouer
+                        }
                     }
                     MenuButtonAction::Simulation => {
-                        println!("Bouton Simulation cliqué !");
-                        next_state.set(AppState::Simulation); // Change l'état
+                        // On refuse de lancer un niveau cassé : on affiche l'erreur à la place
+                        if let Some(error) = selected_level_error(&levels, &selected_level) {
+                            println!("{error}");
+                            for mut text in error_query.iter_mut() {
+                                text.sections[0].value = error.clone();
+                            }
+                        } else {
+                            println!("Bouton Simulation cliqué !");
+                            next_state.set(AppState::Simulation); // Change l'état
+                        }
+                    }
+                    MenuButtonAction::Editor => {
+                        println!("Bouton Editor cliqué !");
+                        next_state.set(AppState::Editor); // Change l'état
                     }
                     MenuButtonAction::Settings => {
                         println!("Bouton Settings cliqué !");
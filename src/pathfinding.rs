@@ -0,0 +1,129 @@
+use std::{cmp::Reverse, collections::{BinaryHeap, VecDeque}};
+use bevy::prelude::Resource;
+
+// Recherche de chemin sur la grille des tuiles (4 voisins, coût de 1 par case)
+
+// Voisins orthogonaux, dans l'ordre Haut, Bas, Gauche, Droite
+pub const NEIGHBORS: [(i32, i32); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+// Cases voisines de (x, y) qui sont dans la grille
+pub fn grid_neighbors(width: usize, height: usize, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+    NEIGHBORS.iter().filter_map(move |&(dx, dy)| {
+        let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+        let inside = nx >= 0 && ny >= 0 && (nx as usize) < width && (ny as usize) < height;
+        inside.then_some((nx as usize, ny as usize))
+    })
+}
+
+// A* avec l'heuristique de Manhattan : retourne les cases de `start` à `goal` (comprises),
+// ou None si `goal` est inaccessible. La case de départ n'a pas besoin d'être libre
+// (un ennemi peut se trouver sur la case d'une tour qui vient d'être posée).
+pub fn astar(
+    width: usize,
+    height: usize,
+    start: (usize, usize),
+    goal: (usize, usize),
+    is_open: impl Fn(usize, usize) -> bool,
+) -> Option<Vec<(usize, usize)>> {
+    let index = |(x, y): (usize, usize)| y * width + x;
+    let heuristic = |(x, y): (usize, usize)| x.abs_diff(goal.0) + y.abs_diff(goal.1);
+
+    let mut cost = vec![usize::MAX; width * height];
+    let mut came_from: Vec<Option<(usize, usize)>> = vec![None; width * height];
+    // Tas trié par (coût estimé, coût réel, case) : le plus petit d'abord
+    let mut open = BinaryHeap::new();
+
+    cost[index(start)] = 0;
+    open.push(Reverse((heuristic(start), 0, start)));
+
+    while let Some(Reverse((_, current_cost, current))) = open.pop() {
+        if current == goal {
+            let mut cells = vec![current];
+            while let Some(previous) = came_from[index(*cells.last().unwrap())] {
+                cells.push(previous);
+            }
+            cells.reverse();
+            return Some(cells);
+        }
+        // Entrée périmée : on a déjà trouvé mieux pour cette case
+        if current_cost > cost[index(current)] { continue; }
+
+        for neighbor in grid_neighbors(width, height, current.0, current.1) {
+            if !is_open(neighbor.0, neighbor.1) { continue; }
+            let new_cost = current_cost + 1;
+            if new_cost < cost[index(neighbor)] {
+                cost[index(neighbor)] = new_cost;
+                came_from[index(neighbor)] = Some(current);
+                open.push(Reverse((new_cost + heuristic(neighbor), new_cost, neighbor)));
+            }
+        }
+    }
+
+    None
+}
+
+// Champ de flux : distance (en cases) de chaque case à l'objectif le plus proche, calculée
+// une seule fois par un parcours en largeur depuis les objectifs. Un ennemi n'a plus qu'à lire
+// la case suivante de celle qu'il vise : le coût par ennemi ne dépend pas de la forme du chemin,
+// et tous les ennemis partagent le même champ, quel que soit le nombre d'objectifs.
+#[derive(Resource, Debug, Clone)]
+pub struct FlowField {
+    pub width: usize,
+    pub height: usize,
+    distance: Vec<u32>,                // u32::MAX = case inaccessible
+    next: Vec<Option<(usize, usize)>>, // Voisin le plus proche d'un objectif (None sur un objectif)
+}
+
+impl FlowField {
+    pub fn new(width: usize, height: usize, goals: &[(usize, usize)], is_open: impl Fn(usize, usize) -> bool) -> Self {
+        let index = |x: usize, y: usize| y * width + x;
+        let mut distance = vec![u32::MAX; width * height];
+        let mut queue = VecDeque::new();
+
+        for &(x, y) in goals.iter() {
+            if x >= width || y >= height { continue; }
+            distance[index(x, y)] = 0;
+            queue.push_back((x, y));
+        }
+
+        while let Some((x, y)) = queue.pop_front() {
+            let current = distance[index(x, y)];
+            for (nx, ny) in grid_neighbors(width, height, x, y) {
+                if !is_open(nx, ny) || distance[index(nx, ny)] != u32::MAX { continue; }
+                distance[index(nx, ny)] = current + 1;
+                queue.push_back((nx, ny));
+            }
+        }
+
+        // Pour chaque case, le voisin qui rapproche le plus d'un objectif
+        let mut next = vec![None; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                let current = distance[index(x, y)];
+                if current == 0 || current == u32::MAX { continue; }
+                next[index(x, y)] = grid_neighbors(width, height, x, y)
+                    .filter(|&(nx, ny)| distance[index(nx, ny)] < current)
+                    .min_by_key(|&(nx, ny)| distance[index(nx, ny)]);
+            }
+        }
+
+        FlowField { width, height, distance, next }
+    }
+
+    // Distance (en cases) jusqu'à l'objectif le plus proche, None si inaccessible
+    pub fn get_distance(&self, x: usize, y: usize) -> Option<u32> {
+        if x >= self.width || y >= self.height { return None; }
+        let distance = self.distance[y * self.width + x];
+        (distance != u32::MAX).then_some(distance)
+    }
+
+    // Case suivante vers l'objectif (None sur un objectif ou une case inaccessible)
+    pub fn get_next_cell(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        if x >= self.width || y >= self.height { return None; }
+        self.next[y * self.width + x]
+    }
+
+    pub fn is_goal(&self, x: usize, y: usize) -> bool {
+        self.get_distance(x, y) == Some(0)
+    }
+}
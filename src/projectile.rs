@@ -1,14 +1,24 @@
 use bevy::prelude::*;
-use crate::{AppState, enemy::Enemy}; // On aura besoin de checker si la cible est un ennemi
+use crate::{AppState, enemy::{Enemy, Health, Resistances}, tower::DamageType, boss::Shield, status::{StatusEffect, StatusEffects}, animation::{AnimationPlayer, AnimationState}}; // On aura besoin de checker si la cible est un ennemi
 
 // Composant Projectile
 #[derive(Component)]
 pub struct Projectile {
     pub target: Entity, // L'entité ennemie visée
     pub damage: i32,
+    pub damage_type: DamageType,
+    pub effects: Vec<StatusEffect>, // Appliqués à la cible touchée
     pub speed: f32,
 }
 
+// Dégâts réellement infligés après résistance. Un coup fait toujours au moins 1 point
+// de dégât, sauf si l'ennemi est immunisé (résistance de 1.0).
+pub fn get_damage(damage: i32, damage_type: DamageType, resistances: &Resistances) -> i32 {
+    let resistance = resistances.get(damage_type).clamp(0.0, 1.0);
+    if resistance >= 1.0 || damage <= 0 { return 0; }
+    ((damage as f32 * (1.0 - resistance)).round() as i32).max(1)
+}
+
 pub struct ProjectilePlugin;
 
 impl Plugin for ProjectilePlugin {
@@ -17,6 +27,7 @@ impl Plugin for ProjectilePlugin {
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn move_projectiles(
     mut commands: Commands,
     mut projectile_query: Query<(Entity, &mut Transform, &Projectile)>,
@@ -24,7 +35,7 @@ fn move_projectiles(
     enemy_query: Query<&GlobalTransform, With<Enemy>>, 
     time: Res<Time>,
     // On a besoin d'accéder à la santé des ennemis pour faire des dégâts
-    mut enemy_health_query: Query<&mut crate::enemy::Health>,
+    mut enemy_health_query: Query<(&mut Health, Option<&Resistances>, Option<&mut Shield>, Option<&mut StatusEffects>, Option<&mut AnimationPlayer>)>,
 ) {
     for (proj_entity, mut proj_transform, projectile) in projectile_query.iter_mut() {
         
@@ -42,8 +53,26 @@ fn move_projectiles(
             // 3. Si on touche la cible (ou qu'on la dépasse)
             if distance <= step {
                 // Appliquer les dégâts
-                if let Ok(mut health) = enemy_health_query.get_mut(projectile.target) {
-                    health.current -= projectile.damage;
+                if let Ok((mut health, resistances, shield, status, animation)) = enemy_health_query.get_mut(projectile.target) {
+                    match shield {
+                        // Le bouclier absorbe le coup entier
+                        Some(mut shield) if shield.hits > 0 => {
+                            shield.hits -= 1;
+                            if shield.hits == 0 { println!("Bouclier brisé !"); }
+                        }
+                        _ => {
+                            let resistances = resistances.copied().unwrap_or_default();
+                            health.current -= get_damage(projectile.damage, projectile.damage_type, &resistances);
+                            if let Some(mut status) = status {
+                                for &effect in projectile.effects.iter() {
+                                    status.apply(effect);
+                                }
+                            }
+                            if let Some(mut animation) = animation {
+                                animation.play(AnimationState::Hit);
+                            }
+                        }
+                    }
                 }
                 
                 // Détruire le projectile
@@ -65,4 +94,38 @@ fn move_projectiles(
             commands.entity(proj_entity).despawn();
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{enemy::EnemyType, tower::TowerType};
+
+    #[test]
+    fn knight_resists_arrows() {
+        let knight = EnemyType::Knight.get_resistances();
+        let damage = get_damage(10, TowerType::Archer.get_damage_type(), &knight);
+        assert!(damage > 0 && damage < 10, "flèche sur chevalier : {damage}");
+    }
+
+    #[test]
+    fn bat_is_immune_to_bombs() {
+        let bat = EnemyType::Bat.get_resistances();
+        assert_eq!(get_damage(25, TowerType::Canon.get_damage_type(), &bat), 0);
+    }
+
+    #[test]
+    fn resisted_hit_does_at_least_one_damage() {
+        let knight = EnemyType::Knight.get_resistances();
+        assert_eq!(get_damage(1, DamageType::Physical, &knight), 1);
+        assert_eq!(get_damage(1, DamageType::Explosive, &knight), 1);
+    }
+
+    #[test]
+    fn unresisted_damage_passes_through() {
+        let orc = EnemyType::Orc.get_resistances();
+        for damage_type in [DamageType::Physical, DamageType::Explosive, DamageType::Magic] {
+            assert_eq!(get_damage(15, damage_type, &orc), 15);
+        }
+        assert_eq!(get_damage(7, DamageType::Magic, &EnemyType::Knight.get_resistances()), 7);
+    }
+}
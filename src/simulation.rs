@@ -2,21 +2,25 @@
 
 use bevy::{
     prelude::*, 
+    ecs::system::EntityCommands,
     sprite::SpriteSheetBundle,
     diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
     ui::node_bundles::AtlasImageBundle,
 };
 use crate::{
-    AppState, GameAssets, level, 
-    game::{Path, GameTile, TileType, get_tile_type, get_atlas_index, spawn_composite_tile},
-    tower::{Tower, TowerType},
-    enemy::{Enemy, Health}, // On n'utilise plus PathFollower du jeu normal
+    AppState, GameAssets, level::GridLayout,
+    game::{TileType, get_tile_type},
+    map::{CurrentMap, MapPlugin, MapSpawnSet, Path, PathRng},
+    tower::{Tower, TowerType, Detector},
+    enemy::{Enemy, Health, EnemyType, get_facing_rotation}, // On n'utilise plus PathFollower du jeu normal
+    stealth::Stealth,
     projectile::Projectile,
+    pathfinding::FlowField,
 };
 
 // --- Composants & Ressources ---
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 struct SimComponent;
 
 #[derive(Component)]
@@ -25,9 +29,37 @@ struct SimStatsText;
 // Composant spécifique pour le mouvement en simulation (permet la boucle infinie)
 #[derive(Component)]
 struct SimPathFollower {
+    segment: usize,
     path_index: usize,
 }
 
+// Variante avec champ de flux : on ne garde que la case visée,
+// la suivante est lue dans le FlowField partagé
+#[derive(Component)]
+struct SimFlowFollower {
+    target: (usize, usize),
+}
+
+// Méthode de déplacement des ennemis du benchmark (touche F pour comparer)
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum SimNavigation {
+    #[default]
+    Waypoints,
+    FlowField,
+}
+
+impl SimNavigation {
+    fn get_label(&self) -> &'static str {
+        match self {
+            SimNavigation::Waypoints => "Waypoints",
+            SimNavigation::FlowField => "Flow field",
+        }
+    }
+}
+
+// Part des ennemis furtifs dans le benchmark
+const SIM_STEALTH_SHARE: f32 = 0.25;
+
 #[derive(Resource)]
 struct SimState {
     start_time: f64,
@@ -43,10 +75,15 @@ pub struct SimulationPlugin;
 impl Plugin for SimulationPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(OnEnter(AppState::Simulation), setup_simulation)
+            // Même carte que le jeu, nettoyée avec le reste de la simulation
+            .add_plugins(MapPlugin { state: AppState::Simulation, marker: SimComponent })
+            .init_resource::<SimNavigation>()
+            .add_systems(OnEnter(AppState::Simulation), setup_simulation.after(MapSpawnSet))
             .add_systems(Update, (
                 spawn_massive_enemies, 
                 move_sim_enemies_loop, // Mouvement avec boucle infinie
+                move_sim_enemies_flow,
+                toggle_sim_navigation,
                 update_performance_ui,
                 log_performance_console
             ).run_if(in_state(AppState::Simulation)))
@@ -56,68 +93,27 @@ impl Plugin for SimulationPlugin {
 
 // --- Setup ---
 
-fn setup_simulation(mut commands: Commands, assets: Res<GameAssets>) {
+fn setup_simulation(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    map: Option<Res<CurrentMap>>,
+    layout: Option<Res<GridLayout>>,
+) {
     println!("=== DÉMARRAGE BENCHMARK (Mode Stress Test Infini) ===");
 
-    let level_data = level::get_level_data();
-    const TILE_SIZE: f32 = 32.0;
-    const MAP_WIDTH: f32 = 20.0 * TILE_SIZE;
-    const MAP_HEIGHT: f32 = 20.0 * TILE_SIZE;
-    let vertical_shift = 50.0;
-    let x_offset = -MAP_WIDTH / 2.0 + TILE_SIZE / 2.0;
-    let y_offset = (MAP_HEIGHT / 2.0 - TILE_SIZE / 2.0) + vertical_shift;
-
-    let mut start_pos = Vec2::ZERO;
+    // Pas de carte : niveau invalide, MapPlugin nous renvoie au menu
+    let (Some(map), Some(layout)) = (map, layout) else { return; };
+    let level_data = &map.level.tiles;
 
-    // 1. Génération Carte + Tours
+    // 1. Placement Tours (Toutes Tier 3) ; la carte et le chemin viennent de MapPlugin
     for (y, row) in level_data.iter().enumerate() {
         for (x, &tile_id) in row.iter().enumerate() {
-            let pos = Vec2::new(
-                x_offset + x as f32 * TILE_SIZE,
-                y_offset - y as f32 * TILE_SIZE,
-            );
-
-            let tile_type = get_tile_type(tile_id);
-            
-            // Helper spawn visuel
-            let mut spawn_tile = |index: usize, rot: Quat, z: f32| {
-                commands.spawn((
-                    SpriteSheetBundle {
-                        texture: assets.sprite_atlas.clone(),
-                        atlas: TextureAtlas { layout: assets.sprite_atlas_layout.clone(), index },
-                        transform: Transform { translation: pos.extend(z), rotation: rot, ..default() },
-                        ..default()
-                    },
-                    SimComponent,
-                ));
-            };
-
-            // Affichage Terrain
-            match tile_id {
-                8..=19 => {
-                    spawn_tile(get_atlas_index(0, 0), Quat::IDENTITY, 0.0);
-                    let (over_idx, rot) = get_composite_info(tile_id);
-                    spawn_tile(over_idx, rot, 0.1);
-                },
-                20 | 21 => {
-                    spawn_tile(get_atlas_index(8, 0), Quat::IDENTITY, 0.0);
-                    let over_idx = if tile_id == 20 { get_atlas_index(7, 2) } else { get_atlas_index(8, 2) };
-                    spawn_tile(over_idx, Quat::IDENTITY, 0.1);
-                },
-                _ => {
-                    let (idx, rot) = get_simple_tile_info(tile_id);
-                    spawn_tile(idx, rot, 0.0);
-                }
-            }
-
-            if tile_id == 20 { start_pos = pos; }
-
-            // Placement Tours (Toutes Tier 3)
-            if tile_type == TileType::Grass {
+            if get_tile_type(tile_id) == TileType::Grass {
+                let pos = layout.grid_to_world(x, y);
                 let tower_type = determine_sim_tower_type(x, y, level_data);
                 let (range, damage, cooldown) = tower_type.get_sim_stats();
 
-                commands.spawn((
+                let mut tower = commands.spawn((
                     SpriteSheetBundle {
                         texture: assets.sprite_atlas.clone(),
                         atlas: TextureAtlas { 
@@ -131,47 +127,20 @@ fn setup_simulation(mut commands: Commands, assets: Res<GameAssets>) {
                         range,
                         damage,
                         cooldown: Timer::from_seconds(cooldown, TimerMode::Repeating),
+                        targets: tower_type.get_targets(),
+                        damage_type: tower_type.get_damage_type(),
+                        on_hit: tower_type.get_on_hit_effects(),
                     },
                     SimComponent,
                 ));
+                if tower_type.is_detector() {
+                    tower.insert(Detector { range });
+                }
             }
         }
     }
 
-    // 2. Pathfinding
-    let mut path_points = Vec::new();
-    path_points.push(start_pos);
-    let mut grid_x = ((start_pos.x - x_offset) / TILE_SIZE).round() as i32;
-    let mut grid_y = ((y_offset - start_pos.y) / TILE_SIZE).round() as i32;
-    let mut last_grid_pos = (grid_x, grid_y);
-
-    for _ in 0..100 {
-        let mut found_next = false;
-        let neighbors = [(0, -1), (0, 1), (-1, 0), (1, 0)];
-        for (dx, dy) in neighbors {
-            let nx = grid_x + dx;
-            let ny = grid_y + dy;
-            if nx < 0 || ny < 0 || nx >= 20 || ny >= 20 { continue; }
-            if (nx, ny) == last_grid_pos { continue; }
-
-            let tid = level_data[ny as usize][nx as usize];
-            let ttype = get_tile_type(tid);
-            if ttype == TileType::Road || ttype == TileType::End {
-                last_grid_pos = (grid_x, grid_y);
-                grid_x = nx;
-                grid_y = ny;
-                path_points.push(Vec2::new(x_offset + nx as f32 * TILE_SIZE, y_offset - ny as f32 * TILE_SIZE));
-                found_next = true;
-                if ttype == TileType::End { break; }
-                break;
-            }
-        }
-        if !found_next { break; }
-        if get_tile_type(level_data[grid_y as usize][grid_x as usize]) == TileType::End { break; }
-    }
-    commands.insert_resource(Path { points: path_points });
-
-    // 3. UI
+    // 2. UI
     commands.spawn((
         NodeBundle {
             style: Style {
@@ -193,7 +162,7 @@ fn setup_simulation(mut commands: Commands, assets: Res<GameAssets>) {
         ));
     });
 
-    // 4. État Initial
+    // 3. État Initial
     commands.insert_resource(SimState {
         start_time: 0.0,
         total_spawned: 0,
@@ -204,15 +173,20 @@ fn setup_simulation(mut commands: Commands, assets: Res<GameAssets>) {
 
 // --- Spawn Massif & Progressif ---
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_massive_enemies(
     mut commands: Commands,
     assets: Res<GameAssets>,
     mut sim_state: ResMut<SimState>,
     path: Res<Path>, // On a besoin du chemin pour la position de départ
     time: Res<Time>,
+    mut rng: ResMut<PathRng>,
+    navigation: Res<SimNavigation>,
+    layout: Option<Res<GridLayout>>,
 ) {
+    let Some(layout) = layout else { return; };
     if sim_state.start_time == 0.0 { sim_state.start_time = time.elapsed_seconds_f64(); }
-    if path.points.is_empty() { return; }
+    if path.is_empty() { return; }
 
     // Augmentation progressive du taux de spawn (Optionnel : Stress Test)
     // Toutes les 10 secondes, on ajoute +1 ennemi par frame (+60/sec)
@@ -221,50 +195,77 @@ fn spawn_massive_enemies(
     let current_spawn_rate = 10 + ramp_up; 
     sim_state.spawn_rate = current_spawn_rate;
 
-    let start_pos = path.points[0];
-
     for _ in 0..current_spawn_rate {
+        // Chaque ennemi part d'un départ tiré au sort
+        let roll = rng.0.next_f32();
+        let Some(segment) = path.choose_start(None, roll) else { return; };
+        let Some(start_pos) = path.get_point(segment, 0) else { return; };
         sim_state.total_spawned += 1;
-        let hp = 85; // 85000 pour test extrême si besoin
-        let speed = 50.0; // Rapide
-
-        commands.spawn((
-            SpriteSheetBundle {
-                texture: assets.sprite_atlas.clone(),
-                atlas: TextureAtlas { layout: assets.sprite_atlas_layout.clone(), index: 10 }, // Orc
-                // CORRECTION 1 : Spawn direct au départ
-                transform: Transform::from_xyz(start_pos.x, start_pos.y, 1.0), 
-                ..default()
-            },
-            Enemy { speed },
-            Health { current: hp, max: hp },
+
+        let mut enemy = spawn_sim_enemy(&mut commands, &assets, start_pos);
+        // Une partie des ennemis est furtive, pour mesurer le coût de la détection
+        if rng.0.next_f32() < SIM_STEALTH_SHARE {
+            enemy.insert(Stealth::default());
+        }
+        match *navigation {
             // CORRECTION 2 : Utilise SimPathFollower pour le mouvement infini
-            SimPathFollower { path_index: 1 }, 
-            SimComponent,
-        ));
+            SimNavigation::Waypoints => { enemy.insert(SimPathFollower { segment, path_index: 1 }); }
+            SimNavigation::FlowField => {
+                let Some(target) = layout.world_to_grid(start_pos) else { continue; };
+                enemy.insert(SimFlowFollower { target });
+            }
+        }
     }
 }
 
+// Orc standard (85 PV, 50 px/s) volontairement réduit au strict nécessaire : pas de barre de vie,
+// d'animation, de récompense ni de résistances comme avec enemy::spawn_enemy, pour que le
+// benchmark mesure toujours le déplacement et les tirs de masse et reste comparable d'une version à l'autre
+fn spawn_sim_enemy<'a>(commands: &'a mut Commands, assets: &GameAssets, position: Vec2) -> EntityCommands<'a> {
+    let enemy_type = EnemyType::Orc;
+    let hp = enemy_type.get_start_health();
+    commands.spawn((
+        SpriteSheetBundle {
+            texture: assets.sprite_atlas.clone(),
+            atlas: TextureAtlas { layout: assets.sprite_atlas_layout.clone(), index: enemy_type.get_sprite_index() },
+            // CORRECTION 1 : Spawn direct au départ
+            transform: Transform::from_xyz(position.x, position.y, 1.0),
+            ..default()
+        },
+        Enemy { speed: enemy_type.get_speed() },
+        Health { current: hp, max: hp },
+        SimComponent,
+    ))
+}
+
 // --- Mouvement Infini (Boucle) ---
 
 fn move_sim_enemies_loop(
     mut query: Query<(&mut Transform, &Enemy, &mut SimPathFollower)>,
     path: Res<Path>,
     time: Res<Time>,
+    mut rng: ResMut<PathRng>,
 ) {
-    if path.points.is_empty() { return; }
+    if path.is_empty() { return; }
 
     for (mut transform, enemy, mut follower) in query.iter_mut() {
-        // Si on dépasse la fin du chemin -> Retour case départ (Boucle infinie)
-        if follower.path_index >= path.points.len() {
+        // Fin du tronçon : branche suivante, ou retour à un départ (Boucle infinie)
+        if path.get_point(follower.segment, follower.path_index).is_none() {
+            let roll = rng.0.next_f32();
             follower.path_index = 1; // On vise le point 1
-            let start = path.points[0];
-            transform.translation.x = start.x;
-            transform.translation.y = start.y;
-            continue;
+            if let Some(next) = path.choose_next(follower.segment, None, roll) {
+                follower.segment = next;
+            } else {
+                let Some(segment) = path.choose_start(None, roll) else { continue; };
+                let Some(start) = path.get_point(segment, 0) else { continue; };
+                follower.segment = segment;
+                transform.translation.x = start.x;
+                transform.translation.y = start.y;
+                continue;
+            }
         }
 
-        let target = path.points[follower.path_index];
+        let Some(target) = path.get_point(follower.segment, follower.path_index) else { continue; };
         let direction = target - transform.translation.truncate();
         let distance = direction.length();
         let step = enemy.speed * time.delta_seconds();
@@ -279,20 +280,88 @@ fn move_sim_enemies_loop(
             transform.translation.y += movement.y;
         }
         
-        // Rotation simplifiée
-        if direction.x.abs() > direction.y.abs() {
-             if direction.x > 0.0 { transform.rotation = Quat::IDENTITY; } 
-             else { transform.rotation = Quat::from_rotation_y(std::f32::consts::PI); }
+        // Même orientation qu'en jeu (enemy::move_enemies)
+        if direction != Vec2::ZERO {
+            transform.rotation = get_facing_rotation(direction);
+        }
+    }
+}
+
+// Même boucle infinie, mais la case suivante vient du champ de flux
+fn move_sim_enemies_flow(
+    mut query: Query<(&mut Transform, &Enemy, &mut SimFlowFollower)>,
+    flow_field: Option<Res<FlowField>>,
+    layout: Option<Res<GridLayout>>,
+    path: Res<Path>,
+    time: Res<Time>,
+    mut rng: ResMut<PathRng>,
+) {
+    let (Some(flow_field), Some(layout)) = (flow_field, layout) else { return; };
+
+    for (mut transform, enemy, mut follower) in query.iter_mut() {
+        let (target_x, target_y) = follower.target;
+        let target = layout.grid_to_world(target_x, target_y);
+        let direction = target - transform.translation.truncate();
+        let distance = direction.length();
+        let step = enemy.speed * time.delta_seconds();
+
+        if distance > step {
+            let movement = direction.normalize() * step;
+            transform.translation.x += movement.x;
+            transform.translation.y += movement.y;
+        } else {
+            transform.translation.x = target.x;
+            transform.translation.y = target.y;
+            match flow_field.get_next_cell(target_x, target_y) {
+                Some(next) => follower.target = next,
+                // Arrivée (ou case hors du champ) : retour à un départ
+                None => {
+                    let roll = rng.0.next_f32();
+                    let Some(segment) = path.choose_start(None, roll) else { continue; };
+                    let Some(start) = path.get_point(segment, 0) else { continue; };
+                    let Some(start_cell) = layout.world_to_grid(start) else { continue; };
+                    transform.translation.x = start.x;
+                    transform.translation.y = start.y;
+                    follower.target = start_cell;
+                }
+            }
+        }
+
+        // Même orientation qu'en jeu (enemy::move_enemies)
+        if direction != Vec2::ZERO {
+            transform.rotation = get_facing_rotation(direction);
         }
     }
 }
 
+// Touche F : change de méthode de déplacement et relance la mesure à zéro,
+// pour comparer les deux méthodes dans les mêmes conditions
+fn toggle_sim_navigation(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut navigation: ResMut<SimNavigation>,
+    mut sim_state: ResMut<SimState>,
+    enemies: Query<Entity, (With<Enemy>, With<SimComponent>)>,
+) {
+    if !keyboard.just_pressed(KeyCode::KeyF) { return; }
+
+    *navigation = match *navigation {
+        SimNavigation::Waypoints => SimNavigation::FlowField,
+        SimNavigation::FlowField => SimNavigation::Waypoints,
+    };
+    for entity in enemies.iter() { commands.entity(entity).despawn_recursive(); }
+    sim_state.start_time = 0.0;
+    sim_state.total_spawned = 0;
+    println!("=== NAVIGATION : {} (mesure relancée) ===", navigation.get_label());
+}
+
 // --- UI et Logging ---
 
 fn update_performance_ui(
     time: Res<Time>,
     diagnostics: Res<DiagnosticsStore>,
     sim_state: Res<SimState>,
+    navigation: Res<SimNavigation>,
     enemies: Query<Entity, With<Enemy>>,
     projectiles: Query<Entity, With<Projectile>>,
     mut text_query: Query<&mut Text, With<SimStatsText>>,
@@ -304,8 +373,8 @@ fn update_performance_ui(
 
     for mut text in text_query.iter_mut() {
         text.sections[0].value = format!(
-            "Temps: {:.1}s\nFPS: {:.1}\nEnnemis Actifs: {}\nTotal Spawnés: {}\nSpawn Rate: {}/frame\nProjectiles: {}",
-            elapsed, fps, active_enemies, sim_state.total_spawned, sim_state.spawn_rate, active_projectiles
+            "Navigation: {} (F)\nTemps: {:.1}s\nFPS: {:.1}\nEnnemis Actifs: {}\nTotal Spawnés: {}\nSpawn Rate: {}/frame\nProjectiles: {}",
+            navigation.get_label(), elapsed, fps, active_enemies, sim_state.total_spawned, sim_state.spawn_rate, active_projectiles
         );
         
         // Change la couleur si les FPS chutent
@@ -318,6 +387,7 @@ fn update_performance_ui(
 fn log_performance_console(
     time: Res<Time>,
     mut sim_state: ResMut<SimState>,
+    navigation: Res<SimNavigation>,
     diagnostics: Res<DiagnosticsStore>,
     enemies: Query<Entity, With<Enemy>>,
 ) {
@@ -325,26 +395,25 @@ fn log_performance_console(
     if now - sim_state.last_log_time >= 1.0 {
         let fps = diagnostics.get(&FrameTimeDiagnosticsPlugin::FPS).and_then(|f| f.smoothed()).unwrap_or(0.0);
         let count = enemies.iter().count();
-        println!("PERF: {:.1}s, Total: {}, FPS: {:.1}, Actifs: {}", 
-            now - sim_state.start_time, sim_state.total_spawned, fps, count);
+        println!("PERF [{}]: {:.1}s, Total: {}, FPS: {:.1}, Actifs: {}", 
+            navigation.get_label(), now - sim_state.start_time, sim_state.total_spawned, fps, count);
         sim_state.last_log_time = now;
     }
 }
 
 fn cleanup_simulation(mut commands: Commands, query: Query<Entity, With<SimComponent>>) {
     for entity in query.iter() { commands.entity(entity).despawn_recursive(); }
-    commands.remove_resource::<Path>();
     commands.remove_resource::<SimState>();
 }
 
-// --- Helpers Visuels (Identiques) ---
-fn determine_sim_tower_type(x: usize, y: usize, level: &[[u32; 20]; 20]) -> TowerType {
+// --- Helpers ---
+fn determine_sim_tower_type(x: usize, y: usize, level: &[Vec<u32>]) -> TowerType {
     let neighbors = [(0, -1), (0, 1), (-1, 0), (1, 0)];
     let mut next_to_road = false;
     for (dx, dy) in neighbors {
         let nx = x as i32 + dx;
         let ny = y as i32 + dy;
-        if nx >= 0 && ny >= 0 && nx < 20 && ny < 20 {
+        if nx >= 0 && ny >= 0 && (nx as usize) < level[0].len() && (ny as usize) < level.len() {
             let tid = level[ny as usize][nx as usize];
             let ttype = get_tile_type(tid);
             if ttype == TileType::Road || ttype == TileType::Start || ttype == TileType::End {
@@ -356,27 +425,3 @@ fn determine_sim_tower_type(x: usize, y: usize, level: &[[u32; 20]; 20]) -> Towe
     if next_to_road { return TowerType::Canon; }
     TowerType::Archer
 }
-
-fn get_composite_info(id: u32) -> (usize, Quat) {
-    let r90 = -90.0f32.to_radians(); let r180 = 180.0f32.to_radians(); let r270 = 90.0f32.to_radians();
-    match id {
-        8 => (get_atlas_index(5, 0), Quat::IDENTITY), 9 => (get_atlas_index(5, 0), Quat::from_rotation_z(r90)),
-        10 => (get_atlas_index(5, 0), Quat::from_rotation_z(r180)), 11 => (get_atlas_index(5, 0), Quat::from_rotation_z(r270)),
-        12 => (get_atlas_index(6, 0), Quat::IDENTITY), 13 => (get_atlas_index(6, 0), Quat::from_rotation_z(r90)),
-        14 => (get_atlas_index(6, 0), Quat::from_rotation_z(r180)), 15 => (get_atlas_index(6, 0), Quat::from_rotation_z(r270)),
-        16 => (get_atlas_index(4, 0), Quat::IDENTITY), 17 => (get_atlas_index(4, 0), Quat::from_rotation_z(r90)),
-        18 => (get_atlas_index(4, 0), Quat::from_rotation_z(r180)), 19 => (get_atlas_index(4, 0), Quat::from_rotation_z(r270)),
-        _ => (0, Quat::IDENTITY),
-    }
-}
-
-fn get_simple_tile_info(id: u32) -> (usize, Quat) {
-    let r90 = -90.0f32.to_radians(); let r180 = 180.0f32.to_radians(); let r270 = 90.0f32.to_radians();
-    match id {
-        0 => (get_atlas_index(9, 0), Quat::IDENTITY), 1 => (get_atlas_index(0, 0), Quat::IDENTITY),
-        2 => (get_atlas_index(8, 0), Quat::IDENTITY), 3 => (get_atlas_index(8, 0), Quat::from_rotation_z(r90)),
-        4 => (get_atlas_index(7, 0), Quat::IDENTITY), 5 => (get_atlas_index(7, 0), Quat::from_rotation_z(r90)),
-        6 => (get_atlas_index(7, 0), Quat::from_rotation_z(r180)), 7 => (get_atlas_index(7, 0), Quat::from_rotation_z(r270)),
-        _ => (get_atlas_index(0, 0), Quat::IDENTITY),
-    }
-}
\ No newline at end of file
@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+use crate::{AppState, enemy::Health};
+
+// Effets temporaires appliqués par les projectiles (voir TowerType::get_on_hit_effects).
+// Le temps est celui du jeu (Time<Virtual>) : quand il est en pause, les effets ne s'écoulent pas.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusKind {
+    Slow,   // Ralentit de `strength` (0.4 = 40 % plus lent)
+    Poison, // `strength` PV par seconde et par cumul
+    Stun,   // Immobilise
+    Burn,   // `strength` PV par seconde
+}
+
+// Ce qui se passe quand un effet déjà actif est appliqué de nouveau
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackRule {
+    // Un seul exemplaire : on garde la plus forte intensité et la plus longue durée
+    Refresh,
+    // Jusqu'à `max` cumuls qui additionnent leurs effets ; la durée repart à zéro
+    Stack { max: u32 },
+}
+
+impl StatusKind {
+    pub fn get_stack_rule(&self) -> StackRule {
+        match self {
+            StatusKind::Slow => StackRule::Refresh,
+            StatusKind::Poison => StackRule::Stack { max: 5 },
+            StatusKind::Stun => StackRule::Refresh,
+            StatusKind::Burn => StackRule::Refresh,
+        }
+    }
+
+    // Couleur de la barre de vie quand l'effet est actif
+    pub fn get_color(&self) -> Color {
+        match self {
+            StatusKind::Slow => Color::rgb(0.4, 0.7, 1.0),
+            StatusKind::Poison => Color::rgb(0.3, 0.9, 0.2),
+            StatusKind::Stun => Color::YELLOW,
+            StatusKind::Burn => Color::ORANGE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub duration: f32, // Secondes restantes
+    pub strength: f32,
+    pub stacks: u32,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, duration: f32, strength: f32) -> Self {
+        StatusEffect { kind, duration, strength, stacks: 1 }
+    }
+
+    // Dégâts par seconde de cet effet (0 pour les effets qui ne blessent pas)
+    pub fn get_damage_per_second(&self) -> f32 {
+        match self.kind {
+            StatusKind::Poison | StatusKind::Burn => self.strength * self.stacks as f32,
+            StatusKind::Slow | StatusKind::Stun => 0.0,
+        }
+    }
+}
+
+// Effets actifs sur un ennemi
+#[derive(Component, Debug, Clone, Default)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+    // Dégâts sur la durée pas encore retirés (on ne retire que des PV entiers)
+    pending_damage: f32,
+}
+
+impl StatusEffects {
+    pub fn get(&self, kind: StatusKind) -> Option<&StatusEffect> {
+        self.effects.iter().find(|effect| effect.kind == kind)
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    // Ajoute un effet en suivant la règle de cumul de son type
+    pub fn apply(&mut self, effect: StatusEffect) {
+        let Some(current) = self.effects.iter_mut().find(|current| current.kind == effect.kind) else {
+            self.effects.push(effect);
+            return;
+        };
+        match effect.kind.get_stack_rule() {
+            StackRule::Refresh => {
+                current.duration = current.duration.max(effect.duration);
+                current.strength = current.strength.max(effect.strength);
+            }
+            StackRule::Stack { max } => {
+                current.stacks = (current.stacks + effect.stacks).min(max);
+                current.duration = effect.duration;
+                current.strength = current.strength.max(effect.strength);
+            }
+        }
+    }
+
+    // Facteur appliqué à la vitesse de l'ennemi : 0 s'il est étourdi
+    pub fn get_speed_multiplier(&self) -> f32 {
+        if self.has(StatusKind::Stun) { return 0.0; }
+        self.get(StatusKind::Slow).map_or(1.0, |slow| (1.0 - slow.strength).clamp(0.0, 1.0))
+    }
+
+    // Teinte de la barre de vie : l'effet le plus marquant l'emporte
+    pub fn get_tint(&self) -> Option<Color> {
+        [StatusKind::Stun, StatusKind::Burn, StatusKind::Poison, StatusKind::Slow].into_iter()
+            .find(|&kind| self.has(kind))
+            .map(|kind| kind.get_color())
+    }
+
+    // Avance le temps de `delta` secondes : retire les effets terminés et
+    // retourne les PV entiers perdus par poison et brûlure
+    pub fn tick(&mut self, delta: f32) -> i32 {
+        for effect in self.effects.iter_mut() {
+            // Un effet qui se termine pendant la frame ne blesse que jusqu'à sa fin
+            self.pending_damage += effect.get_damage_per_second() * delta.min(effect.duration);
+            effect.duration -= delta;
+        }
+        self.effects.retain(|effect| effect.duration > 0.0);
+
+        let damage = self.pending_damage.floor();
+        self.pending_damage -= damage;
+        if self.effects.is_empty() { self.pending_damage = 0.0; }
+        damage as i32
+    }
+}
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, tick_status_effects
+            .run_if(in_state(AppState::Playing).or_else(in_state(AppState::Simulation))));
+    }
+}
+
+fn tick_status_effects(time: Res<Time>, mut query: Query<(&mut StatusEffects, &mut Health)>) {
+    let delta = time.delta_seconds();
+    if delta <= 0.0 { return; }
+    for (mut status, mut health) in query.iter_mut() {
+        if status.effects.is_empty() { continue; }
+        let damage = status.tick(delta);
+        health.current -= damage;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refresh_keeps_longest_and_strongest() {
+        let mut status = StatusEffects::default();
+        status.apply(StatusEffect::new(StatusKind::Slow, 3.0, 0.2));
+        status.apply(StatusEffect::new(StatusKind::Slow, 1.0, 0.5));
+        let slow = status.get(StatusKind::Slow).unwrap();
+        assert_eq!((slow.duration, slow.strength, slow.stacks), (3.0, 0.5, 1));
+        assert_eq!(status.effects.len(), 1);
+    }
+
+    #[test]
+    fn stacks_are_capped() {
+        let mut status = StatusEffects::default();
+        for _ in 0..10 {
+            status.apply(StatusEffect::new(StatusKind::Poison, 2.0, 1.0));
+        }
+        let StackRule::Stack { max } = StatusKind::Poison.get_stack_rule() else { panic!("le poison se cumule"); };
+        assert_eq!(status.get(StatusKind::Poison).unwrap().stacks, max);
+    }
+
+    #[test]
+    fn paused_time_changes_nothing() {
+        let mut status = StatusEffects::default();
+        status.apply(StatusEffect::new(StatusKind::Burn, 2.0, 5.0));
+        status.apply(StatusEffect::new(StatusKind::Stun, 0.5, 1.0));
+        assert_eq!(status.tick(0.0), 0);
+        assert_eq!(status.get(StatusKind::Burn).unwrap().duration, 2.0);
+        assert_eq!(status.get(StatusKind::Stun).unwrap().duration, 0.5);
+    }
+
+    #[test]
+    fn overshooting_tick_stops_damage_at_effect_end() {
+        let mut status = StatusEffects::default();
+        // 10 PV par seconde pendant 0,5 s : 5 PV, même si la frame dure 2 s
+        status.apply(StatusEffect::new(StatusKind::Burn, 0.5, 10.0));
+        assert_eq!(status.tick(2.0), 5);
+        assert!(status.effects.is_empty());
+        assert_eq!(status.tick(1.0), 0);
+    }
+}
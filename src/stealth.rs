@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+use crate::{AppState, enemy::Enemy, game::TowerShootingSet, tower::Detector};
+
+// Ennemi invisible pour les tours, sauf s'il est à portée d'un détecteur
+#[derive(Component, Default)]
+pub struct Stealth {
+    pub revealed: bool,
+}
+
+// Transparence d'un ennemi furtif, selon qu'il est repéré ou non
+const HIDDEN_ALPHA: f32 = 0.3;
+const REVEALED_ALPHA: f32 = 0.8;
+
+pub struct StealthPlugin;
+
+impl Plugin for StealthPlugin {
+    fn build(&self, app: &mut App) {
+        // Aussi en simulation : le coût de la détection apparaît dans le benchmark.
+        // Avant les tirs, pour qu'aucune tour ne vise un ennemi qui vient de sortir de portée
+        app.add_systems(Update, detect_stealth_enemies
+            .before(TowerShootingSet)
+            .run_if(in_state(AppState::Playing).or_else(in_state(AppState::Simulation))));
+    }
+}
+
+// Un ennemi furtif est repéré tant qu'il est à portée d'au moins un détecteur
+fn detect_stealth_enemies(
+    detectors: Query<(&Transform, &Detector)>,
+    mut enemies: Query<(&Transform, &mut Stealth, &mut Sprite), With<Enemy>>,
+) {
+    for (transform, mut stealth, mut sprite) in enemies.iter_mut() {
+        let position = transform.translation.truncate();
+        let revealed = detectors.iter().any(|(detector_transform, detector)| {
+            detector_transform.translation.truncate().distance_squared(position) <= detector.range * detector.range
+        });
+
+        // On ne touche aux composants que si l'état change
+        if stealth.revealed != revealed || stealth.is_added() {
+            stealth.revealed = revealed;
+            sprite.color.set_a(if revealed { REVEALED_ALPHA } else { HIDDEN_ALPHA });
+        }
+    }
+}
@@ -0,0 +1,672 @@
+use bevy::{
+    prelude::*,
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+use xml::reader::{EventReader, XmlEvent};
+use crate::{
+    level::{BranchWeight, Level, TerrainCell, TerrainModifier, MAX_TILE_ID, TILE_SIZE},
+    map::get_tile_sprites,
+};
+
+// Import des cartes faites avec l'éditeur Tiled (https://www.mapeditor.org), au format
+// XML (.tmx) ou JSON (.tmj), placées dans "assets/levels" à côté des fichiers .level.ron.
+//
+// - Le premier tileset de la carte doit être spriteatlas.png (10x3 tuiles de 32px) :
+//   la tuile locale n de ce tileset est l'index n de l'atlas (voir get_atlas_index).
+// - Les calques de tuiles sont superposés : pour chaque case on garde la tuile du calque
+//   le plus haut (ex : un calque "sol" avec l'eau et un calque "surcouche" avec les rives).
+// - Les rotations de Tiled (touche Z) donnent la rotation des virages et des rives.
+// - Calque d'objets : "start" / "spawn" (point de départ), "end" (arrivée), "branch"
+//   (propriété "weight" : poids de la branche qui commence sur cette case) et "flight"
+//   (points de passage des ennemis volants, dans l'ordre des objets), et "mud", "bridge"
+//   ou "boost" (modificateur de vitesse de la case de route).
+//   Le type de l'objet est lu dans sa classe, ou dans son nom si elle est vide.
+// - Propriété "name" de la carte : nom du niveau (sinon le nom du fichier), propriété "waves" :
+//   vagues d'ennemis au format RON (voir wave.rs).
+
+// Bits de retournement stockés dans les gids par Tiled
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+const ROTATED_HEXAGONAL_120: u32 = 0x1000_0000;
+const FLIP_FLAGS: u32 = FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY | ROTATED_HEXAGONAL_120;
+
+// Chemin relatif de l'atlas utilisé à l'export (les cartes exportées vont dans "assets/tiled")
+const ATLAS_IMAGE: &str = "../spriteatlas.png";
+
+#[derive(Debug, Error)]
+pub enum TiledError {
+    #[error("impossible de lire la carte Tiled : {0}")]
+    Io(#[from] std::io::Error),
+    #[error("carte Tiled JSON invalide : {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("carte Tiled XML invalide : {0}")]
+    Xml(#[from] xml::reader::Error),
+    #[error("attribut \"{0}\" manquant ou invalide")]
+    MissingAttribute(&'static str),
+    #[error("encodage de calque non supporté : {0} (utiliser CSV)")]
+    UnsupportedEncoding(String),
+    #[error("la carte n'a aucun calque de tuiles")]
+    NoTileLayer,
+    #[error("un calque n'a pas la taille de la carte ({width}x{height})")]
+    LayerSize { width: usize, height: usize },
+    #[error("tuile Tiled {gid} en ({x},{y}) sans équivalent dans le jeu")]
+    UnmappedTile { gid: u32, x: usize, y: usize },
+    #[error("objet \"{name}\" en dehors de la carte")]
+    MarkerOutsideMap { name: String },
+    #[error("propriété \"waves\" invalide : {0}")]
+    Waves(#[from] ron::error::SpannedError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TiledFormat {
+    Tmx,
+    Tmj,
+}
+
+impl TiledFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "tmx" => Some(TiledFormat::Tmx),
+            "tmj" | "json" => Some(TiledFormat::Tmj),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TiledLoader;
+
+impl AssetLoader for TiledLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = TiledError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Level, TiledError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let path = load_context.path();
+            let format = path.extension()
+                .and_then(|extension| TiledFormat::from_extension(&extension.to_string_lossy()))
+                .unwrap_or(TiledFormat::Tmx);
+            let default_name = path.file_stem().map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+            parse_tiled(&bytes, format, &default_name)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx", "tmj"]
+    }
+}
+
+// --- Carte Tiled, indépendante du format de fichier ---
+
+#[derive(Default)]
+struct TiledMap {
+    width: usize,
+    height: usize,
+    tile_width: f32,
+    tile_height: f32,
+    first_gid: u32,
+    name: Option<String>,
+    waves: Option<String>, // Vagues au format RON (propriété "waves" de la carte)
+    // Gids de chaque calque de tuiles, ligne par ligne, du calque le plus bas au plus haut
+    tile_layers: Vec<Vec<u32>>,
+    objects: Vec<TiledObject>,
+}
+
+#[derive(Default)]
+struct TiledObject {
+    name: String,
+    class: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    // Les objets "tuile" ont leur origine en bas à gauche au lieu d'en haut à gauche
+    is_tile: bool,
+    weight: Option<f32>,
+}
+
+impl TiledObject {
+    fn kind(&self) -> String {
+        let kind = if self.class.is_empty() { &self.name } else { &self.class };
+        kind.to_lowercase()
+    }
+}
+
+pub fn parse_tiled(bytes: &[u8], format: TiledFormat, default_name: &str) -> Result<Level, TiledError> {
+    let map = match format {
+        TiledFormat::Tmx => parse_tmx(bytes)?,
+        TiledFormat::Tmj => parse_tmj(bytes)?,
+    };
+    build_level(map, default_name)
+}
+
+fn build_level(map: TiledMap, default_name: &str) -> Result<Level, TiledError> {
+    let (width, height) = (map.width, map.height);
+    if map.tile_layers.is_empty() {
+        return Err(TiledError::NoTileLayer);
+    }
+    if width == 0 || height == 0 || map.tile_layers.iter().any(|layer| layer.len() != width * height) {
+        return Err(TiledError::LayerSize { width, height });
+    }
+
+    // Tuile du calque le plus haut ; de l'eau là où aucun calque n'a de tuile
+    let mut tiles = vec![vec![1; width]; height];
+    for (y, row) in tiles.iter_mut().enumerate() {
+        for (x, tile) in row.iter_mut().enumerate() {
+            let top_gid = map.tile_layers.iter().rev()
+                .map(|layer| layer[y * width + x])
+                .find(|gid| gid & !FLIP_FLAGS != 0);
+            if let Some(gid) = top_gid {
+                *tile = tile_from_gid(gid, map.first_gid).ok_or(TiledError::UnmappedTile { gid, x, y })?;
+            }
+        }
+    }
+
+    // Marqueurs du calque d'objets
+    let mut branch_weights = Vec::new();
+    let mut flight_path = Vec::new();
+    let mut modifiers = Vec::new();
+    for object in map.objects.iter() {
+        let kind = object.kind();
+        let modifier = TerrainModifier::from_name(&kind);
+        if !matches!(kind.as_str(), "start" | "spawn" | "end" | "branch" | "flight") && modifier.is_none() { continue; }
+
+        // Case sous le centre de l'objet
+        let center_x = object.x + object.width / 2.0;
+        let center_y = if object.is_tile { object.y - object.height / 2.0 } else { object.y + object.height / 2.0 };
+        let (grid_x, grid_y) = ((center_x / map.tile_width).floor(), (center_y / map.tile_height).floor());
+        if grid_x < 0.0 || grid_y < 0.0 || grid_x as usize >= width || grid_y as usize >= height {
+            return Err(TiledError::MarkerOutsideMap { name: object.name.clone() });
+        }
+        let (x, y) = (grid_x as usize, grid_y as usize);
+
+        if let Some(modifier) = modifier {
+            modifiers.push(TerrainCell { x, y, modifier });
+            continue;
+        }
+        match kind.as_str() {
+            "start" | "spawn" => tiles[y][x] = 20,
+            "end" => tiles[y][x] = 21,
+            "flight" => flight_path.push((x, y)),
+            _ => branch_weights.push(BranchWeight { x, y, weight: object.weight.unwrap_or(1.0) }),
+        }
+    }
+
+    let mut level = Level::new(map.name.unwrap_or_else(|| default_name.to_string()), tiles);
+    level.branch_weights = branch_weights;
+    level.flight_path = flight_path;
+    level.modifiers = modifiers;
+    if let Some(waves) = map.waves {
+        level.waves = ron::from_str(&waves)?;
+    }
+    Ok(level)
+}
+
+// --- Correspondance gid Tiled <-> id de tuile du jeu ---
+
+// Nombre de quarts de tour dans le sens horaire d'une rotation de get_tile_sprites
+fn clockwise_turns(rotation: Quat) -> u32 {
+    let angle = 2.0 * rotation.z.atan2(rotation.w);
+    ((-angle / std::f32::consts::FRAC_PI_2).round() as i32).rem_euclid(4) as u32
+}
+
+// Sprite visible d'une tuile : la surcouche si elle en a une, sinon la tuile de base
+fn top_sprite(tile_id: u32) -> (usize, u32) {
+    let (base, overlay) = get_tile_sprites(tile_id);
+    let (index, rotation) = overlay.unwrap_or(base);
+    (index, clockwise_turns(rotation))
+}
+
+fn tile_from_gid(gid: u32, first_gid: u32) -> Option<u32> {
+    let local_id = (gid & !FLIP_FLAGS).checked_sub(first_gid)?;
+    let flipped = |flag: u32| gid & flag != 0;
+
+    // Tiled code les rotations avec des retournements ; un simple miroir n'a pas d'équivalent
+    let turns = match (flipped(FLIPPED_HORIZONTALLY), flipped(FLIPPED_VERTICALLY), flipped(FLIPPED_DIAGONALLY)) {
+        (false, false, false) => 0,
+        (true, false, true) => 1,
+        (true, true, false) => 2,
+        (false, true, true) => 3,
+        _ => return None,
+    };
+
+    let candidates: Vec<(u32, u32)> = (0..=MAX_TILE_ID)
+        .filter_map(|tile_id| {
+            let (index, tile_turns) = top_sprite(tile_id);
+            (index == local_id as usize).then_some((tile_id, tile_turns))
+        })
+        .collect();
+
+    candidates.iter().find(|&&(_, tile_turns)| tile_turns == turns)
+        // Route droite : un demi-tour donne le même sprite
+        .or_else(|| candidates.iter().find(|&&(_, tile_turns)| tile_turns == (turns + 2) % 4))
+        // Sprite qui n'existe que dans un sens (herbe, eau, lettres S et E)
+        .or_else(|| if candidates.len() == 1 { candidates.first() } else { None })
+        .map(|&(tile_id, _)| tile_id)
+}
+
+fn gid_from_sprite(index: usize, rotation: Quat, first_gid: u32) -> u32 {
+    let flags = match clockwise_turns(rotation) {
+        1 => FLIPPED_HORIZONTALLY | FLIPPED_DIAGONALLY,
+        2 => FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY,
+        3 => FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY,
+        _ => 0,
+    };
+    (first_gid + index as u32) | flags
+}
+
+// --- Lecture TMX (XML) ---
+
+fn parse_tmx(bytes: &[u8]) -> Result<TiledMap, TiledError> {
+    let mut map = TiledMap::default();
+    // Éléments ouverts, du plus externe au plus interne
+    let mut stack: Vec<String> = Vec::new();
+    let mut layer_gids: Vec<u32> = Vec::new();
+    let mut object: Option<TiledObject> = None;
+
+    for event in EventReader::new(bytes) {
+        match event? {
+            XmlEvent::StartElement { name, attributes, .. } => {
+                let attr = |key: &str| {
+                    attributes.iter().find(|a| a.name.local_name == key).map(|a| a.value.clone())
+                };
+                let parent = stack.last().map(String::as_str);
+                let grandparent = stack.len().checked_sub(2).map(|i| stack[i].as_str());
+
+                match name.local_name.as_str() {
+                    "map" => {
+                        map.width = parse_attr(attr("width"), "width")?;
+                        map.height = parse_attr(attr("height"), "height")?;
+                        map.tile_width = parse_attr(attr("tilewidth"), "tilewidth")?;
+                        map.tile_height = parse_attr(attr("tileheight"), "tileheight")?;
+                    }
+                    "tileset" if map.first_gid == 0 => {
+                        map.first_gid = parse_attr(attr("firstgid"), "firstgid")?;
+                    }
+                    "data" => {
+                        if let Some(encoding) = attr("encoding").filter(|encoding| encoding != "csv") {
+                            return Err(TiledError::UnsupportedEncoding(encoding));
+                        }
+                        layer_gids.clear();
+                    }
+                    // Calque sans encodage : une balise <tile gid="..."/> par case
+                    "tile" if parent == Some("data") => {
+                        layer_gids.push(attr("gid").and_then(|gid| gid.parse().ok()).unwrap_or(0));
+                    }
+                    // Les objets des tuiles d'un tileset (collisions) ne sont pas des marqueurs
+                    "object" if parent == Some("objectgroup") && grandparent != Some("tile") => {
+                        object = Some(TiledObject {
+                            name: attr("name").unwrap_or_default(),
+                            class: attr("class").or_else(|| attr("type")).unwrap_or_default(),
+                            x: parse_attr(attr("x"), "x").unwrap_or(0.0),
+                            y: parse_attr(attr("y"), "y").unwrap_or(0.0),
+                            width: parse_attr(attr("width"), "width").unwrap_or(0.0),
+                            height: parse_attr(attr("height"), "height").unwrap_or(0.0),
+                            is_tile: attr("gid").is_some(),
+                            weight: None,
+                        });
+                    }
+                    "property" if parent == Some("properties") => {
+                        let value = attr("value");
+                        match (grandparent, attr("name").as_deref()) {
+                            (Some("map"), Some("name")) => map.name = value,
+                            (Some("map"), Some("waves")) => map.waves = value,
+                            (Some("object"), Some("weight")) => {
+                                if let Some(object) = object.as_mut() {
+                                    object.weight = value.and_then(|value| value.parse().ok());
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    _ => {}
+                }
+                stack.push(name.local_name);
+            }
+            XmlEvent::Characters(text) if stack.last().map(String::as_str) == Some("data") => {
+                for gid in text.split(',').map(str::trim).filter(|gid| !gid.is_empty()) {
+                    layer_gids.push(gid.parse().map_err(|_| TiledError::MissingAttribute("data"))?);
+                }
+            }
+            XmlEvent::EndElement { name } => {
+                stack.pop();
+                match name.local_name.as_str() {
+                    "data" => map.tile_layers.push(std::mem::take(&mut layer_gids)),
+                    "object" => map.objects.extend(object.take()),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if map.first_gid == 0 { map.first_gid = 1; }
+    Ok(map)
+}
+
+fn parse_attr<T: std::str::FromStr>(value: Option<String>, attribute: &'static str) -> Result<T, TiledError> {
+    value.and_then(|value| value.trim().parse().ok()).ok_or(TiledError::MissingAttribute(attribute))
+}
+
+// --- Lecture TMJ (JSON) ---
+
+#[derive(Deserialize)]
+struct TmjMap {
+    width: usize,
+    height: usize,
+    tilewidth: f32,
+    tileheight: f32,
+    #[serde(default)]
+    layers: Vec<TmjLayer>,
+    #[serde(default)]
+    tilesets: Vec<TmjTileset>,
+    #[serde(default)]
+    properties: Vec<TmjProperty>,
+}
+
+#[derive(Deserialize)]
+struct TmjLayer {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    encoding: Option<String>,
+    // Tableau de gids (CSV), ou chaîne base64 non supportée
+    #[serde(default)]
+    data: Option<serde_json::Value>,
+    #[serde(default)]
+    objects: Vec<TmjObject>,
+    // Calques enfants d'un groupe
+    #[serde(default)]
+    layers: Vec<TmjLayer>,
+}
+
+#[derive(Deserialize)]
+struct TmjTileset {
+    firstgid: u32,
+}
+
+#[derive(Deserialize)]
+struct TmjObject {
+    #[serde(default)]
+    name: String,
+    // "type" avant Tiled 1.9, "class" ensuite
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    class: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    gid: Option<u32>,
+    #[serde(default)]
+    properties: Vec<TmjProperty>,
+}
+
+#[derive(Deserialize)]
+struct TmjProperty {
+    name: String,
+    value: serde_json::Value,
+}
+
+fn parse_tmj(bytes: &[u8]) -> Result<TiledMap, TiledError> {
+    let tmj: TmjMap = serde_json::from_slice(bytes)?;
+    let mut map = TiledMap {
+        width: tmj.width,
+        height: tmj.height,
+        tile_width: tmj.tilewidth,
+        tile_height: tmj.tileheight,
+        first_gid: tmj.tilesets.first().map_or(1, |tileset| tileset.firstgid),
+        name: get_tmj_string(&tmj.properties, "name"),
+        waves: get_tmj_string(&tmj.properties, "waves"),
+        ..default()
+    };
+    add_tmj_layers(&mut map, tmj.layers)?;
+    Ok(map)
+}
+
+fn get_tmj_string(properties: &[TmjProperty], name: &str) -> Option<String> {
+    properties.iter()
+        .find(|property| property.name == name)
+        .and_then(|property| property.value.as_str().map(str::to_string))
+}
+
+fn add_tmj_layers(map: &mut TiledMap, layers: Vec<TmjLayer>) -> Result<(), TiledError> {
+    for layer in layers {
+        match layer.kind.as_str() {
+            "tilelayer" => {
+                let gids = layer.data.as_ref()
+                    .and_then(|data| data.as_array())
+                    .map(|gids| gids.iter().map(|gid| gid.as_u64().unwrap_or(0) as u32).collect());
+                match gids {
+                    Some(gids) => map.tile_layers.push(gids),
+                    None => return Err(TiledError::UnsupportedEncoding(layer.encoding.unwrap_or_default())),
+                }
+            }
+            "objectgroup" => {
+                map.objects.extend(layer.objects.into_iter().map(|object| TiledObject {
+                    weight: object.properties.iter()
+                        .find(|property| property.name == "weight")
+                        .and_then(|property| property.value.as_f64())
+                        .map(|weight| weight as f32),
+                    name: object.name,
+                    class: if object.class.is_empty() { object.kind } else { object.class },
+                    x: object.x,
+                    y: object.y,
+                    width: object.width,
+                    height: object.height,
+                    is_tile: object.gid.is_some(),
+                }));
+            }
+            "group" => add_tmj_layers(map, layer.layers)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// --- Export TMJ ---
+
+// Écrit un niveau au format JSON de Tiled : un calque "sol", un calque "surcouche"
+// (rives, lettres) et un calque d'objets avec le départ, l'arrivée, les poids des branches,
+// le trajet des ennemis volants et les modificateurs de terrain
+pub fn export_tmj(level: &Level) -> Result<String, serde_json::Error> {
+    let first_gid = 1;
+    let tile_size = TILE_SIZE as u32;
+    let (width, height) = (level.width(), level.height());
+
+    let mut ground = Vec::with_capacity(width * height);
+    let mut overlay = Vec::with_capacity(width * height);
+    let mut objects = Vec::new();
+    let point = |x: usize, y: usize| ((x as f32 + 0.5) * TILE_SIZE, (y as f32 + 0.5) * TILE_SIZE);
+
+    for (y, row) in level.tiles.iter().enumerate() {
+        for (x, &tile_id) in row.iter().enumerate() {
+            // Départ et arrivée : route dans le calque de tuiles + objet marqueur
+            let marker = match tile_id {
+                20 => Some("start"),
+                21 => Some("end"),
+                _ => None,
+            };
+            let shown_id = if marker.is_some() { 2 } else { tile_id };
+            let ((base_index, base_rotation), top) = get_tile_sprites(shown_id);
+
+            ground.push(gid_from_sprite(base_index, base_rotation, first_gid));
+            overlay.push(top.map_or(0, |(index, rotation)| gid_from_sprite(index, rotation, first_gid)));
+
+            if let Some(kind) = marker {
+                let (px, py) = point(x, y);
+                objects.push(json!({
+                    "id": objects.len() + 1, "name": kind, "type": kind,
+                    "x": px, "y": py, "width": 0, "height": 0, "point": true,
+                    "rotation": 0, "visible": true,
+                }));
+            }
+        }
+    }
+
+    for branch in level.branch_weights.iter() {
+        let (px, py) = point(branch.x, branch.y);
+        objects.push(json!({
+            "id": objects.len() + 1, "name": "branch", "type": "branch",
+            "x": px, "y": py, "width": 0, "height": 0, "point": true,
+            "rotation": 0, "visible": true,
+            "properties": [{ "name": "weight", "type": "float", "value": branch.weight }],
+        }));
+    }
+
+    for &(x, y) in level.flight_path.iter() {
+        let (px, py) = point(x, y);
+        objects.push(json!({
+            "id": objects.len() + 1, "name": "flight", "type": "flight",
+            "x": px, "y": py, "width": 0, "height": 0, "point": true,
+            "rotation": 0, "visible": true,
+        }));
+    }
+
+    for cell in level.modifiers.iter() {
+        let (px, py) = point(cell.x, cell.y);
+        let kind = cell.modifier.get_name();
+        objects.push(json!({
+            "id": objects.len() + 1, "name": kind, "type": kind,
+            "x": px, "y": py, "width": 0, "height": 0, "point": true,
+            "rotation": 0, "visible": true,
+        }));
+    }
+
+    let tile_layer = |id: u32, name: &str, data: Vec<u32>| json!({
+        "id": id, "name": name, "type": "tilelayer",
+        "width": width, "height": height, "x": 0, "y": 0,
+        "opacity": 1, "visible": true, "data": data,
+    });
+
+    // Les vagues n'ont pas d'équivalent dans Tiled : on les garde en RON dans une propriété
+    let mut properties = vec![json!({ "name": "name", "type": "string", "value": level.name })];
+    if !level.waves.is_empty() {
+        let waves = ron::to_string(&level.waves).map_err(serde::ser::Error::custom)?;
+        properties.push(json!({ "name": "waves", "type": "string", "value": waves }));
+    }
+
+    let map = json!({
+        "type": "map",
+        "version": "1.10",
+        "orientation": "orthogonal",
+        "renderorder": "right-down",
+        "infinite": false,
+        "width": width,
+        "height": height,
+        "tilewidth": tile_size,
+        "tileheight": tile_size,
+        "nextlayerid": 4,
+        "nextobjectid": objects.len() + 1,
+        "properties": properties,
+        "tilesets": [{
+            "firstgid": first_gid,
+            "name": "spriteatlas",
+            "image": ATLAS_IMAGE,
+            "imagewidth": 10 * tile_size,
+            "imageheight": 3 * tile_size,
+            "tilewidth": tile_size,
+            "tileheight": tile_size,
+            "tilecount": 30,
+            "columns": 10,
+            "margin": 0,
+            "spacing": 0,
+        }],
+        "layers": [
+            tile_layer(1, "sol", ground),
+            tile_layer(2, "surcouche", overlay),
+            {
+                "id": 3, "name": "marqueurs", "type": "objectgroup",
+                "opacity": 1, "visible": true, "x": 0, "y": 0,
+                "objects": objects,
+            },
+        ],
+    });
+    serde_json::to_string_pretty(&map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wave::default_waves;
+
+    #[test]
+    fn export_then_import_keeps_the_level() {
+        // Tous les ids de tuile, sur deux lignes
+        let ids: Vec<u32> = (0..=MAX_TILE_ID).collect();
+        let tiles: Vec<Vec<u32>> = ids.chunks(ids.len().div_ceil(2)).map(|row| row.to_vec()).collect();
+        let mut level = Level::new("Aller-retour", tiles);
+        level.branch_weights = vec![BranchWeight { x: 2, y: 0, weight: 2.5 }];
+        level.flight_path = vec![(3, 1), (0, 0)];
+        level.modifiers = TerrainModifier::ALL.iter().enumerate()
+            .map(|(x, &modifier)| TerrainCell { x, y: 1, modifier })
+            .collect();
+        level.waves = default_waves();
+
+        let json = export_tmj(&level).unwrap();
+        let imported = parse_tiled(json.as_bytes(), TiledFormat::Tmj, "défaut").unwrap();
+        assert_eq!(imported.name, level.name);
+        assert_eq!(imported.tiles, level.tiles);
+        assert_eq!(imported.branch_weights, level.branch_weights);
+        assert_eq!(imported.flight_path, level.flight_path);
+        assert_eq!(imported.modifiers, level.modifiers);
+        assert_eq!(imported.waves, level.waves);
+    }
+
+    #[test]
+    fn small_csv_tmx_map() {
+        // Herbe avec une route verticale (gid tourné d'un quart de tour) au-dessus d'une route droite.
+        // L'arrivée est un objet tuile : son origine est le coin en bas à gauche.
+        let tmx = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="3" height="2" tilewidth="32" tileheight="32">
+ <properties>
+  <property name="name" value="Petit niveau"/>
+ </properties>
+ <tileset firstgid="1" name="spriteatlas" tilewidth="32" tileheight="32" tilecount="30" columns="10"/>
+ <layer id="1" name="sol" width="3" height="2">
+  <data encoding="csv">
+10,2684354569,10,
+9,9,9
+</data>
+ </layer>
+ <objectgroup id="2" name="marqueurs">
+  <object id="1" name="start" x="16" y="48"><point/></object>
+  <object id="2" name="end" gid="29" x="64" y="64" width="32" height="32"/>
+  <object id="3" name="boue" class="mud" x="32" y="32" width="32" height="32"/>
+ </objectgroup>
+</map>"#;
+        let level = parse_tiled(tmx.as_bytes(), TiledFormat::Tmx, "défaut").unwrap();
+        assert_eq!(level.name, "Petit niveau");
+        assert_eq!(level.tiles, vec![vec![0, 3, 0], vec![20, 2, 21]]);
+        assert_eq!(level.modifiers, vec![TerrainCell { x: 1, y: 1, modifier: TerrainModifier::Mud }]);
+    }
+
+    #[test]
+    fn mirrored_gid_is_rejected() {
+        let gid = FLIPPED_HORIZONTALLY | 9;
+        let tmx = format!(r#"<map width="1" height="1" tilewidth="32" tileheight="32">
+ <tileset firstgid="1"/>
+ <layer><data encoding="csv">{gid}</data></layer>
+</map>"#);
+        let result = parse_tiled(tmx.as_bytes(), TiledFormat::Tmx, "défaut");
+        assert!(matches!(result, Err(TiledError::UnmappedTile { gid: g, x: 0, y: 0 }) if g == gid), "{result:?}");
+    }
+}
@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use crate::status::{StatusEffect, StatusKind};
 
 // Les 3 types de tours dans le jeu
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
@@ -14,6 +15,37 @@ pub struct Tower {
     pub range: f32,
     pub damage: i32,
     pub cooldown: Timer,
+    pub targets: TargetFlags,
+    pub damage_type: DamageType,
+    pub on_hit: Vec<StatusEffect>, // Effets appliqués par chaque projectile
+}
+
+// Tour qui révèle les ennemis furtifs (stealth::Stealth) à moins de `range` pixels :
+// toutes les tours peuvent alors les viser
+#[derive(Component)]
+pub struct Detector {
+    pub range: f32,
+}
+
+// Type de dégâts d'une tour, réduit par les résistances de l'ennemi (enemy::Resistances)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageType {
+    Physical,  // Flèches
+    Explosive, // Boulets
+    Magic,     // Sorts
+}
+
+// Quels ennemis une tour peut viser : au sol, en l'air, ou les deux
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetFlags {
+    pub ground: bool,
+    pub air: bool,
+}
+
+impl TargetFlags {
+    pub fn can_target(&self, flying: bool) -> bool {
+        if flying { self.air } else { self.ground }
+    }
 }
 
 impl TowerType {
@@ -43,6 +75,40 @@ impl TowerType {
         }
     }
 
+    // Les boulets du canon ne touchent pas les ennemis volants
+    pub fn get_targets(&self) -> TargetFlags {
+        match self {
+            TowerType::Canon => TargetFlags { ground: true, air: false },
+            TowerType::Archer => TargetFlags { ground: true, air: true },
+            TowerType::Wizard => TargetFlags { ground: true, air: true },
+        }
+    }
+
+    pub fn get_damage_type(&self) -> DamageType {
+        match self {
+            TowerType::Canon => DamageType::Explosive,
+            TowerType::Archer => DamageType::Physical,
+            TowerType::Wizard => DamageType::Magic,
+        }
+    }
+
+    // Flèches empoisonnées, boulets qui assomment et enflamment, sorts de glace
+    pub fn get_on_hit_effects(&self) -> Vec<StatusEffect> {
+        match self {
+            TowerType::Canon => vec![
+                StatusEffect::new(StatusKind::Stun, 0.5, 0.0),
+                StatusEffect::new(StatusKind::Burn, 2.0, 4.0),
+            ],
+            TowerType::Archer => vec![StatusEffect::new(StatusKind::Poison, 4.0, 2.0)],
+            TowerType::Wizard => vec![StatusEffect::new(StatusKind::Slow, 2.0, 0.4)],
+        }
+    }
+
+    // Le sorcier voit les ennemis furtifs sur toute sa portée
+    pub fn is_detector(&self) -> bool {
+        *self == TowerType::Wizard
+    }
+
     pub fn get_cost(&self) -> i32 {
         match self {
             TowerType::Canon => 65,
@@ -0,0 +1,338 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{
+    AppState,
+    GameAssets,
+    map::{CurrentMap, MapSpawnSet, Path, PathRng},
+    enemy::{Enemy, EnemyType, Dying, SpawnOnDeath, spawn_enemy_on_path},
+    boss::{BossType, spawn_boss},
+    stealth::Stealth,
+};
+
+// Vagues d'ennemis, décrites dans le niveau (champ `waves` du .level.ron) :
+//
+// waves: [
+//     (delay: 15.0, groups: [
+//         (enemy: Orc, count: 6, interval: 1.5),
+//         (enemy: Bat, count: 2, interval: 2.0, delay: 4.0, spawn: Some(1)),
+//         (enemy: Knight, count: 1, interval: 0.0, delay: 8.0, boss: Some(Shielded)),
+//         (enemy: Orc, count: 9, interval: 1.0, boss: Some(Healer), boss_every: Some(3)),
+//         (enemy: Wolf, count: 3, interval: 1.0, delay: 10.0, stealth: true),
+//         (enemy: Orc, count: 2, interval: 1.5, split: Some((enemy: Orc, count: 2, scale: 0.6))),
+//     ]),
+// ]
+
+// Un groupe d'ennemis identiques dans une vague
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnemyGroup {
+    pub enemy: EnemyType,
+    pub count: u32,
+    // Secondes entre deux apparitions
+    pub interval: f32,
+    // Secondes entre le début de la vague et la première apparition du groupe
+    #[serde(default)]
+    pub delay: f32,
+    // Départ imposé (index parmi les départs du niveau), sinon tirage au sort
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spawn: Option<usize>,
+    // Branche imposée à chaque embranchement, sinon tirage pondéré
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<usize>,
+    // Les ennemis du groupe sont des boss avec cette capacité (voir boss::spawn_boss)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boss: Option<BossType>,
+    // Avec `boss`, seul un ennemi sur N du groupe est un boss (le Nᵉ, le 2Nᵉ...), les autres sont normaux
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boss_every: Option<u32>,
+    // Ennemis furtifs : seules les tours proches d'un détecteur peuvent les viser
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub stealth: bool,
+    // À sa mort, chaque ennemi du groupe se scinde en ennemis plus petits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split: Option<SpawnOnDeath>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl EnemyGroup {
+    pub fn new(enemy: EnemyType, count: u32, interval: f32, delay: f32) -> Self {
+        EnemyGroup { enemy, count, interval, delay, spawn: None, branch: None, boss: None, boss_every: None, stealth: false, split: None }
+    }
+
+    pub fn stealthy(enemy: EnemyType, count: u32, interval: f32, delay: f32) -> Self {
+        EnemyGroup { stealth: true, ..EnemyGroup::new(enemy, count, interval, delay) }
+    }
+
+    pub fn splitting(enemy: EnemyType, count: u32, interval: f32, delay: f32, split: SpawnOnDeath) -> Self {
+        EnemyGroup { split: Some(split), ..EnemyGroup::new(enemy, count, interval, delay) }
+    }
+
+    // Un seul boss, `delay` secondes après le début de la vague
+    pub fn boss(enemy: EnemyType, boss: BossType, delay: f32) -> Self {
+        EnemyGroup { boss: Some(boss), ..EnemyGroup::new(enemy, 1, 0.0, delay) }
+    }
+
+    // `count` ennemis dont un sur `every` est un boss
+    pub fn with_boss_every(enemy: EnemyType, count: u32, interval: f32, delay: f32, boss: BossType, every: u32) -> Self {
+        EnemyGroup { boss: Some(boss), boss_every: Some(every), ..EnemyGroup::new(enemy, count, interval, delay) }
+    }
+
+    // Le `index`-ième ennemi du groupe (à partir de 0) est-il un boss ?
+    fn is_boss(&self, index: u32) -> bool {
+        if self.boss.is_none() { return false; }
+        match self.boss_every {
+            Some(every) if every > 0 => index % every == every - 1,
+            _ => true,
+        }
+    }
+
+    // Nombre d'ennemis du groupe apparus `elapsed` secondes après le début de la vague
+    fn get_due(&self, elapsed: f32) -> u32 {
+        if elapsed < self.delay { return 0; }
+        if self.interval <= 0.0 { return self.count; }
+        (((elapsed - self.delay) / self.interval) as u32 + 1).min(self.count)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaveDefinition {
+    // Durée (secondes) de la phase de construction avant la vague ; le joueur peut l'écourter
+    #[serde(default)]
+    pub delay: f32,
+    pub groups: Vec<EnemyGroup>,
+}
+
+impl WaveDefinition {
+    pub fn get_enemy_count(&self) -> u32 {
+        self.groups.iter().map(|group| group.count).sum()
+    }
+}
+
+// Vagues utilisées par les niveaux qui n'en définissent pas (des boss dans les vagues 2 à 5)
+pub fn default_waves() -> Vec<WaveDefinition> {
+    use EnemyType::*;
+    vec![
+        WaveDefinition { delay: 15.0, groups: vec![EnemyGroup::new(Orc, 6, 1.5, 0.0)] },
+        WaveDefinition { delay: 10.0, groups: vec![
+            EnemyGroup::new(Orc, 8, 1.2, 0.0),
+            EnemyGroup::new(Wolf, 4, 0.8, 6.0),
+            EnemyGroup::boss(Orc, BossType::Healer, 8.0),
+        ] },
+        WaveDefinition { delay: 10.0, groups: vec![
+            EnemyGroup::with_boss_every(Orc, 6, 1.2, 0.0, BossType::Healer, 3),
+            EnemyGroup::new(Bat, 4, 2.0, 3.0),
+            EnemyGroup::stealthy(Wolf, 3, 1.0, 8.0),
+        ] },
+        WaveDefinition { delay: 10.0, groups: vec![
+            EnemyGroup::new(Knight, 4, 2.5, 0.0),
+            EnemyGroup::new(Wolf, 6, 0.8, 5.0),
+            EnemyGroup::boss(Knight, BossType::Shielded, 3.0),
+            EnemyGroup::splitting(Orc, 3, 1.5, 8.0, SpawnOnDeath::new(Orc, 2, 0.6)),
+        ] },
+        WaveDefinition { delay: 10.0, groups: vec![
+            EnemyGroup::new(Orc, 10, 0.8, 0.0),
+            EnemyGroup::new(Knight, 4, 2.0, 4.0),
+            EnemyGroup::new(Bat, 5, 1.5, 8.0),
+            EnemyGroup::boss(Orc, BossType::Necromancer, 10.0),
+        ] },
+    ]
+}
+
+// Or gagné par seconde de construction sautée en lançant la vague en avance
+pub const EARLY_CALL_GOLD_PER_SECOND: f32 = 2.0;
+
+// Une vague est terminée quand tous ses ennemis sont apparus puis ont disparu (tués ou arrivés)
+#[derive(Event, Debug, Clone, Copy)]
+pub struct WaveCleared {
+    pub wave: usize,
+}
+
+// La dernière vague est terminée
+#[derive(Event, Debug, Clone, Copy)]
+pub struct AllWavesCleared;
+
+// Déroulement des vagues de la partie en cours
+#[derive(Resource, Debug)]
+pub struct WaveManager {
+    pub waves: Vec<WaveDefinition>,
+    pub current: usize, // Vague en cours (== waves.len() quand tout est fini)
+    pub elapsed: f32,   // Secondes depuis le début de la vague en cours, phase de construction comprise
+    spawned: Vec<u32>,  // Ennemis déjà apparus, par groupe de la vague en cours
+}
+
+impl WaveManager {
+    pub fn new(waves: Vec<WaveDefinition>) -> Self {
+        let mut manager = WaveManager { waves, current: 0, elapsed: 0.0, spawned: Vec::new() };
+        manager.start_wave(0);
+        manager
+    }
+
+    pub fn get_current_wave(&self) -> Option<&WaveDefinition> {
+        self.waves.get(self.current)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current >= self.waves.len()
+    }
+
+    // Tous les ennemis de la vague en cours sont apparus
+    pub fn is_wave_spawned(&self) -> bool {
+        let Some(wave) = self.get_current_wave() else { return true; };
+        wave.groups.iter().zip(self.spawned.iter()).all(|(group, &spawned)| spawned >= group.count)
+    }
+
+    // Secondes restantes de la phase de construction (0 une fois la vague lancée)
+    pub fn get_build_time_left(&self) -> f32 {
+        let Some(wave) = self.get_current_wave() else { return 0.0; };
+        (wave.delay - self.elapsed).max(0.0)
+    }
+
+    pub fn is_build_phase(&self) -> bool {
+        self.get_build_time_left() > 0.0
+    }
+
+    // Or gagné si la vague était lancée maintenant
+    pub fn get_early_call_bonus(&self) -> i32 {
+        (self.get_build_time_left() * EARLY_CALL_GOLD_PER_SECOND).floor() as i32
+    }
+
+    // Termine la phase de construction tout de suite et retourne le bonus correspondant
+    pub fn call_early(&mut self) -> i32 {
+        let bonus = self.get_early_call_bonus();
+        self.elapsed += self.get_build_time_left();
+        bonus
+    }
+
+    fn start_wave(&mut self, index: usize) {
+        self.current = index;
+        self.elapsed = 0.0;
+        self.spawned = self.get_current_wave().map_or(Vec::new(), |wave| vec![0; wave.groups.len()]);
+    }
+
+    // Vague suivante (après WaveCleared)
+    pub fn next_wave(&mut self) {
+        self.start_wave(self.current + 1);
+    }
+
+    // Avance le temps de `delta` secondes et retourne les groupes dont un ennemi doit apparaître,
+    // avec `boss` à None quand cet ennemi-là n'est pas un boss
+    pub fn tick(&mut self, delta: f32) -> Vec<EnemyGroup> {
+        let Some(wave) = self.waves.get(self.current) else { return Vec::new(); };
+        self.elapsed += delta;
+        let elapsed = self.elapsed - wave.delay;
+
+        let mut to_spawn = Vec::new();
+        for (group, spawned) in wave.groups.iter().zip(self.spawned.iter_mut()) {
+            while *spawned < group.get_due(elapsed) {
+                let boss = group.boss.filter(|_| group.is_boss(*spawned));
+                to_spawn.push(EnemyGroup { boss, ..group.clone() });
+                *spawned += 1;
+            }
+        }
+        to_spawn
+    }
+}
+
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<WaveCleared>()
+            .add_event::<AllWavesCleared>()
+            .add_systems(OnEnter(AppState::Playing), setup_waves.after(MapSpawnSet))
+            .add_systems(Update, (
+                spawn_wave_enemies,
+                // Après les apparitions de la frame, pour ne pas conclure trop tôt
+                check_wave_cleared.after(spawn_wave_enemies),
+            ).run_if(in_state(AppState::Playing).and_then(resource_exists::<WaveManager>)))
+            .add_systems(OnExit(AppState::Playing), cleanup_waves);
+    }
+}
+
+fn setup_waves(mut commands: Commands, map: Option<Res<CurrentMap>>) {
+    // Pas de carte : niveau invalide, MapPlugin nous renvoie au menu
+    let Some(map) = map else { return; };
+    let waves = if map.level.waves.is_empty() { default_waves() } else { map.level.waves.clone() };
+    println!("{} vague(s) au programme", waves.len());
+    commands.insert_resource(WaveManager::new(waves));
+}
+
+fn spawn_wave_enemies(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    path: Res<Path>,
+    time: Res<Time>,
+    mut waves: ResMut<WaveManager>,
+    mut rng: ResMut<PathRng>,
+) {
+    for group in waves.tick(time.delta_seconds()) {
+        let roll = rng.0.next_f32();
+        let Some(segment) = path.choose_start(group.spawn, roll) else { continue; };
+        let enemy = match group.boss {
+            Some(boss) => spawn_boss(&mut commands, &assets, &path, group.enemy, boss, segment, group.branch),
+            None => spawn_enemy_on_path(&mut commands, &assets, &path, group.enemy, segment, group.branch),
+        };
+        let Some(enemy) = enemy else { continue; };
+        if group.stealth {
+            commands.entity(enemy).insert(Stealth::default());
+        }
+        if let Some(split) = group.split {
+            commands.entity(enemy).insert(split);
+        }
+    }
+}
+
+// Les ennemis qui meurent comptent encore : un ennemi qui se scinde (SpawnOnDeath)
+// fait apparaître ses enfants pendant son animation de mort
+#[allow(clippy::type_complexity)]
+fn check_wave_cleared(
+    mut waves: ResMut<WaveManager>,
+    enemies: Query<(), Or<(With<Enemy>, With<Dying>)>>,
+    mut cleared_events: EventWriter<WaveCleared>,
+    mut finished_events: EventWriter<AllWavesCleared>,
+) {
+    if waves.is_finished() || !waves.is_wave_spawned() || !enemies.is_empty() { return; }
+
+    let wave = waves.current;
+    println!("Vague {} terminée !", wave + 1);
+    cleared_events.send(WaveCleared { wave });
+
+    waves.next_wave();
+    if waves.is_finished() {
+        println!("Toutes les vagues sont terminées : victoire !");
+        finished_events.send(AllWavesCleared);
+    }
+}
+
+// Les ennemis n'ont pas le marqueur du jeu : on retire ceux qui restent en quittant la partie
+// (y compris ceux qui jouaient encore leur animation de mort)
+#[allow(clippy::type_complexity)]
+fn cleanup_waves(mut commands: Commands, enemies: Query<Entity, Or<(With<Enemy>, With<Dying>)>>) {
+    for entity in enemies.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<WaveManager>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boss_every_nth_spawn() {
+        let group = EnemyGroup::with_boss_every(EnemyType::Orc, 7, 1.0, 0.0, BossType::Healer, 3);
+        let mut waves = WaveManager::new(vec![WaveDefinition { delay: 0.0, groups: vec![group] }]);
+        let bosses: Vec<bool> = waves.tick(10.0).iter().map(|group| group.boss.is_some()).collect();
+        assert_eq!(bosses, [false, false, true, false, false, true, false]);
+    }
+
+    #[test]
+    fn boss_group_without_interval_is_all_bosses() {
+        let group = EnemyGroup { count: 2, ..EnemyGroup::boss(EnemyType::Knight, BossType::Shielded, 0.0) };
+        let mut waves = WaveManager::new(vec![WaveDefinition { delay: 0.0, groups: vec![group] }]);
+        assert!(waves.tick(1.0).iter().all(|group| group.boss == Some(BossType::Shielded)));
+    }
+}